@@ -0,0 +1,75 @@
+use core::ptr;
+
+pub type Activation = fn(f32) -> f32;
+pub type Aggregation = fn(&[f32]) -> f32;
+
+/// Every activation function that a node can be given, along with the name it's saved under.
+///
+/// New functions only ever go at the end, since encoded genomes refer to them by their position.
+pub const ACTIVATIONS: [(&str, Activation); 8] = [
+    ("identity", identity),
+    ("relu", relu),
+    ("clamped", clamped),
+    ("abs", abs),
+    ("sine", sine),
+    ("gaussian", gaussian),
+    ("sigmoid", sigmoid),
+    ("tanh", tanh),
+];
+
+/// Every aggregation function that a node can be given, along with the name it's saved under.
+pub const AGGREGATIONS: [(&str, Aggregation); 5] = [
+    ("sum", sum),
+    ("mean", mean),
+    ("product", product),
+    ("max", max),
+    ("min", min),
+];
+
+// functions are named by looking up their address, which only works if every function has just
+// the one copy that isn't inlined into some codegen unit of its own
+#[inline(never)]
+pub fn identity(x: f32) -> f32 { x }
+#[inline(never)]
+pub fn relu(x: f32) -> f32 { f32::max(x, 0.0) }
+#[inline(never)]
+pub fn clamped(x: f32) -> f32 { x.clamp(-1.0, 1.0) }
+#[inline(never)]
+pub fn abs(x: f32) -> f32 { x.abs() }
+
+// the periodic and symmetric functions are what let a cppn paint repeating and mirrored patterns
+#[inline(never)]
+pub fn sine(x: f32) -> f32 { libm::sinf(x) }
+#[inline(never)]
+pub fn gaussian(x: f32) -> f32 { libm::expf(-x * x) }
+#[inline(never)]
+pub fn sigmoid(x: f32) -> f32 { 1.0 / (1.0 + libm::expf(-x)) }
+#[inline(never)]
+pub fn tanh(x: f32) -> f32 { libm::tanhf(x) }
+
+#[inline(never)]
+pub fn sum(values: &[f32]) -> f32 { values.iter().sum() }
+#[inline(never)]
+pub fn mean(values: &[f32]) -> f32 { values.iter().sum::<f32>() / (values.len() as f32) }
+#[inline(never)]
+pub fn product(values: &[f32]) -> f32 { values.iter().product() }
+#[inline(never)]
+pub fn max(values: &[f32]) -> f32 { values.iter().copied().fold(f32::NEG_INFINITY, f32::max) }
+#[inline(never)]
+pub fn min(values: &[f32]) -> f32 { values.iter().copied().fold(f32::INFINITY, f32::min) }
+
+pub fn activation_name(activ: Activation) -> Option<&'static str> {
+    ACTIVATIONS.iter().find(|(_, f)| ptr::fn_addr_eq(*f, activ)).map(|(name, _)| *name)
+}
+
+pub fn aggregation_name(aggreg: Aggregation) -> Option<&'static str> {
+    AGGREGATIONS.iter().find(|(_, f)| ptr::fn_addr_eq(*f, aggreg)).map(|(name, _)| *name)
+}
+
+pub fn activation(name: &str) -> Option<Activation> {
+    ACTIVATIONS.iter().find(|(curr, _)| *curr == name).map(|(_, f)| *f)
+}
+
+pub fn aggregation(name: &str) -> Option<Aggregation> {
+    AGGREGATIONS.iter().find(|(curr, _)| *curr == name).map(|(_, f)| *f)
+}
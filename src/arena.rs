@@ -1,5 +1,5 @@
 extern crate alloc;
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec::Vec};
 use core::{cell::Cell, mem::MaybeUninit, ptr::NonNull};
 
 #[derive(Debug)]
@@ -9,31 +9,37 @@ pub(crate) struct Arena<T, const N: usize = 32> {
 
 impl<T, const N: usize> Arena<T, N> {
     pub(crate) fn push<'a>(&self, value: T) -> &'a T {
-        if let Some(mut curr) = self.curr.get() {
-            if let Some(uninit) = {
-                let curr: &'a mut Chunk<T, N> = unsafe { curr.as_mut() };
-                let uninit = curr.buf.get_mut(curr.len);
-                curr.len += 1;
-                uninit
-            } { uninit.write(value) } else {
+        let mut curr = match self.curr.get() {
+            Some(curr) if unsafe { curr.as_ref().len } < N => curr,
+            prev => {
                 let mut new = Box::into_non_null(Box::new(Chunk::<T, N>::default()));
+                // set new prev to curr
+                unsafe { new.as_mut().prev = prev; }
                 // set curr to new
                 self.curr.set(Some(new));
-                // set new prev to curr
-                unsafe { new.as_mut().prev = Some(curr); }
-                // push value to new
-                // return pushed value
-                unsafe { new.as_mut().buf.first_mut().unwrap_unchecked().write(value) }
+                new
             }
-        } else {
-            let mut new = Box::into_non_null(Box::new(Chunk::<T, N>::default()));
-            // set curr to new
-            self.curr.set(Some(new));
-            unsafe { new.as_mut().len += 1; }
-            // push value to new
-            // return pushed value
-            unsafe { new.as_mut().buf.first_mut().unwrap_unchecked().write(value) }
+        };
+
+        // push value to curr and return pushed value
+        let curr: &'a mut Chunk<T, N> = unsafe { curr.as_mut() };
+        let uninit = &mut curr.buf[curr.len];
+        curr.len += 1;
+        uninit.write(value)
+    }
+
+    /// Iterates over the values in the order they were pushed.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> + Clone {
+        let mut chunks = Vec::new();
+        let mut curr = self.curr.get();
+
+        while let Some(chunk) = curr {
+            let chunk = unsafe { chunk.as_ref() };
+            chunks.push(chunk);
+            curr = chunk.prev;
         }
+
+        chunks.into_iter().rev().flat_map(|chunk| unsafe { chunk.buf[..chunk.len].assume_init_ref() })
     }
 }
 
@@ -44,6 +50,18 @@ impl<T, const N: usize> Default for Arena<T, N> {
     }
 }
 
+impl<T, const N: usize> Drop for Arena<T, N> {
+    fn drop(&mut self) {
+        let mut curr = self.curr.take();
+
+        while let Some(chunk) = curr {
+            let mut chunk = unsafe { Box::from_non_null(chunk) };
+            unsafe { chunk.buf[..chunk.len].assume_init_drop(); }
+            curr = chunk.prev;
+        }
+    }
+}
+
 struct Chunk<T, const N: usize> {
     buf: [MaybeUninit<T>; N],
     len: usize,
@@ -53,10 +71,9 @@ struct Chunk<T, const N: usize> {
 impl<T, const N: usize> Default for Chunk<T, N> {
     fn default() -> Self {
         Self {
-            buf: MaybeUninit::uninit_array::<N>(),
+            buf: [const { MaybeUninit::uninit() }; N],
             len: 0,
             prev: None,
         }
     }
 }
-
@@ -0,0 +1,132 @@
+extern crate alloc;
+use crate::{conf::Conf, fitness::Fitness, genome::Genome, lineage::Lineage, pop::*, repr::*, species::Species};
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GenomeState {
+    pub genome: GenomeRepr,
+    pub fitness: f32,
+    pub age: usize,
+    pub parent_species: Option<usize>,
+    pub lineage: Lineage,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpeciesState {
+    pub id: usize,
+    pub parent: Option<usize>,
+    pub repr: GenomeRepr,
+    pub members: Vec<usize>,
+    pub best: f32,
+    pub stagnation: usize,
+}
+
+/// Everything needed to carry on with a run of a [`Pop`] exactly where it left off, including
+/// its innovation numbers.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Checkpoint {
+    pub conf: Conf,
+    pub generation: usize,
+    pub genomes: Vec<GenomeState>,
+    pub species: Vec<SpeciesState>,
+    /// Innovation number of every edge, keyed by the innovation numbers of its tail and head,
+    /// in the order they were handed out.
+    pub edge_innovs: Vec<(usize, usize, usize)>,
+    pub node_innovs: usize,
+    pub next_species: usize,
+    pub next_genome: usize,
+    pub seed: u64,
+    /// Number of random number generators that the population had handed out.
+    pub streams: u64,
+}
+
+impl<'a, const I: usize, const O: usize> Pop<'a, I, O> {
+    /// Saves the population along with its innovation numbers.
+    pub fn checkpoint(&self) -> Checkpoint {
+        let mut edge_innovs = self.tracker.edges.iter()
+            .map(|((tail, head), innov)| (*tail, *head, *innov))
+            .collect::<Vec<_>>();
+
+        edge_innovs.sort_by_key(|(.., innov)| *innov);
+
+        Checkpoint {
+            conf: self.conf.clone(),
+            generation: self.generation,
+            genomes: self.genomes.iter().map(|genome| GenomeState {
+                genome: GenomeRepr::from(genome),
+                fitness: *genome.fitness,
+                age: genome.age,
+                parent_species: genome.parent_species,
+                lineage: genome.lineage.clone(),
+            }).collect(),
+            species: self.species.iter().map(|species| SpeciesState {
+                id: species.id,
+                parent: species.parent,
+                repr: GenomeRepr::from(&species.repr),
+                members: species.members.clone(),
+                best: *species.best,
+                stagnation: species.stagnation,
+            }).collect(),
+            edge_innovs,
+            node_innovs: self.tracker.nodes,
+            next_species: self.next_species,
+            next_genome: self.next_genome,
+            seed: self.seed,
+            streams: self.streams,
+        }
+    }
+
+    /// Rebuilds the population saved in `checkpoint`, along with the innovation numbers it was
+    /// saved with. The innovation numbers of the current thread are left alone.
+    pub fn resume(checkpoint: &Checkpoint) -> Result<Self, ReprError> {
+        let mut tracker = Tracker {
+            edges: checkpoint.edge_innovs.iter().map(|(tail, head, innov)| ((*tail, *head), *innov)).collect(),
+            nodes: checkpoint.node_innovs,
+        };
+
+        // rebuilding a genome reserves its node innovation numbers, which belong to the population
+        // rather than to the thread
+        let (genomes, species) = tracker.within(|| {
+            let genomes = checkpoint.genomes.iter()
+                .map(|state| {
+                    let mut genome = Genome::try_from(&state.genome)?;
+                    genome.fitness = Fitness::from(state.fitness);
+                    genome.age = state.age;
+                    genome.parent_species = state.parent_species;
+                    genome.lineage = state.lineage.clone();
+                    Ok(genome)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let species = checkpoint.species.iter()
+                .map(|state| Ok(Species {
+                    id: state.id,
+                    parent: state.parent,
+                    repr: Genome::try_from(&state.repr)?,
+                    members: state.members.clone(),
+                    best: Fitness::from(state.best),
+                    stagnation: state.stagnation,
+                }))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok::<_, ReprError>((genomes, species))
+        })?;
+
+        Ok(Self {
+            genomes,
+            species,
+            conf: checkpoint.conf.clone(),
+            generation: checkpoint.generation,
+            next_species: checkpoint.next_species,
+            next_genome: checkpoint.next_genome,
+            seed: checkpoint.seed,
+            streams: checkpoint.streams,
+            tracker,
+        })
+    }
+}
@@ -0,0 +1,224 @@
+extern crate alloc;
+use crate::{activ, repr::*};
+use core::{error, fmt};
+use alloc::{string::ToString, vec::Vec};
+
+/// Bytes that every encoded genome starts with.
+pub const MAGIC: [u8; 4] = *b"NEAT";
+
+/// Major and minor version of the format written by [`GenomeRepr::encode`]. Minor versions only
+/// ever add trailing fields, so a reader accepts every minor version of its own major version.
+pub const VERSION: [u8; 2] = [1, 1];
+
+// tag of the trailing field that marks which edges are recurrent, packed like the enabled bits
+const RECURRENT: usize = 0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodeError {
+    Magic,
+    Version(u8),
+    Truncated,
+    Varint,
+    UnknownActivation(usize),
+    UnknownAggregation(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Magic => write!(f, "not an encoded genome"),
+            Self::Version(major) => write!(f, "unsupported major version {major}"),
+            Self::Truncated => write!(f, "encoded genome ends early"),
+            Self::Varint => write!(f, "varint doesn't fit in a usize"),
+            Self::UnknownActivation(idx) => write!(f, "unknown activation function {idx}"),
+            Self::UnknownAggregation(idx) => write!(f, "unknown aggregation function {idx}"),
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+impl GenomeRepr {
+    /// Encodes the genome into a compact binary format:
+    ///
+    /// - [`MAGIC`] and [`VERSION`]
+    /// - the number of inputs, outputs, nodes and edges
+    /// - every node as its innovation number, then its bias, response and tau, then the index of
+    ///   its activation and aggregation function in [`activ::ACTIVATIONS`] and
+    ///   [`activ::AGGREGATIONS`]
+    /// - every edge as its tail, head and innovation number, then its weight
+    /// - whether each edge is enabled, packed eight to a byte with the first edge in the lowest bit
+    /// - any number of trailing fields, each a tag and a length followed by that many bytes
+    ///
+    /// Counts, indices and innovation numbers are LEB128 varints and floats are little endian.
+    /// Version 1.0 doesn't write any trailing fields. Version 1.1 adds a field with tag 0 that
+    /// packs whether each edge is recurrent the same way as whether it's enabled, and edges read
+    /// without it aren't recurrent.
+    pub fn encode(&self) -> Result<Vec<u8>, ReprError> {
+        let mut buf = Vec::from(MAGIC);
+        buf.extend(VERSION);
+
+        for len in [self.inputs, self.outputs, self.nodes.len(), self.edges.len()] {
+            write_varint(&mut buf, len);
+        }
+
+        for node in self.nodes.iter() {
+            let activation = activ::ACTIVATIONS.iter().position(|(name, _)| *name == node.activation)
+                .ok_or_else(|| ReprError::UnknownActivation(node.activation.clone()))?;
+
+            let aggregation = activ::AGGREGATIONS.iter().position(|(name, _)| *name == node.aggregation)
+                .ok_or_else(|| ReprError::UnknownAggregation(node.aggregation.clone()))?;
+
+            write_varint(&mut buf, node.innov);
+
+            for value in [node.bias, node.response, node.tau] {
+                buf.extend(value.to_le_bytes());
+            }
+
+            write_varint(&mut buf, activation);
+            write_varint(&mut buf, aggregation);
+        }
+
+        for edge in self.edges.iter() {
+            for value in [edge.tail, edge.head, edge.innov] {
+                write_varint(&mut buf, value);
+            }
+
+            buf.extend(edge.weight.to_le_bytes());
+        }
+
+        buf.extend(pack(&self.edges, |edge| edge.enabled));
+
+        let recurrent = pack(&self.edges, |edge| edge.recurrent);
+        write_varint(&mut buf, RECURRENT);
+        write_varint(&mut buf, recurrent.len());
+        buf.extend(recurrent);
+
+        Ok(buf)
+    }
+
+    /// Decodes a genome written by [`GenomeRepr::encode`], skipping any trailing fields that this
+    /// version doesn't know about.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader(bytes);
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(DecodeError::Magic);
+        }
+
+        let [major, _minor] = [reader.byte()?, reader.byte()?];
+
+        if major != VERSION[0] {
+            return Err(DecodeError::Version(major));
+        }
+
+        let [inputs, outputs, nodes, edges] = [reader.varint()?, reader.varint()?, reader.varint()?, reader.varint()?];
+
+        let nodes = (0..nodes)
+            .map(|_| {
+                let innov = reader.varint()?;
+                let [bias, response, tau] = [reader.f32()?, reader.f32()?, reader.f32()?];
+
+                let activation = reader.varint()?;
+                let (activation, _) = activ::ACTIVATIONS.get(activation).ok_or(DecodeError::UnknownActivation(activation))?;
+
+                let aggregation = reader.varint()?;
+                let (aggregation, _) = activ::AGGREGATIONS.get(aggregation).ok_or(DecodeError::UnknownAggregation(aggregation))?;
+
+                Ok(NodeGene {
+                    innov,
+                    bias,
+                    response,
+                    tau,
+                    activation: activation.to_string(),
+                    aggregation: aggregation.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut edges = (0..edges)
+            .map(|_| {
+                let [tail, head, innov] = [reader.varint()?, reader.varint()?, reader.varint()?];
+                Ok(EdgeGene { tail, head, weight: reader.f32()?, enabled: false, innov, recurrent: false })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let flags = reader.take(edges.len().div_ceil(8))?;
+
+        for (idx, edge) in edges.iter_mut().enumerate() {
+            edge.enabled = unpack(flags, idx);
+        }
+
+        // fields that this version doesn't know about are skipped
+        while !reader.0.is_empty() {
+            let [tag, len] = [reader.varint()?, reader.varint()?];
+            let field = reader.take(len)?;
+
+            if tag == RECURRENT {
+                if field.len() < edges.len().div_ceil(8) {
+                    return Err(DecodeError::Truncated);
+                }
+
+                for (idx, edge) in edges.iter_mut().enumerate() {
+                    edge.recurrent = unpack(field, idx);
+                }
+            }
+        }
+
+        Ok(Self { inputs, outputs, nodes, edges })
+    }
+}
+
+// packs one bit per edge eight to a byte, with the first edge in the lowest bit
+fn pack(edges: &[EdgeGene], flag: impl Fn(&EdgeGene) -> bool) -> Vec<u8> {
+    edges.chunks(8)
+        .map(|chunk| chunk.iter().enumerate().fold(0, |byte, (bit, edge)| byte | ((flag(edge) as u8) << bit)))
+        .collect()
+}
+
+fn unpack(flags: &[u8], idx: usize) -> bool {
+    (flags[idx / 8] >> (idx % 8)) & 1 == 1
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+
+    buf.push(value as u8);
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let (taken, rest) = self.0.split_at_checked(len).ok_or(DecodeError::Truncated)?;
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn f32(&mut self) -> Result<f32, DecodeError> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn varint(&mut self) -> Result<usize, DecodeError> {
+        let mut value = 0usize;
+
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.byte()?;
+            let bits = ((byte & 0x7f) as usize).checked_shl(shift).filter(|bits| bits >> shift == (byte & 0x7f) as usize);
+            value |= bits.ok_or(DecodeError::Varint)?;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(DecodeError::Varint)
+    }
+}
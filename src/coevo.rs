@@ -0,0 +1,100 @@
+extern crate alloc;
+use crate::{conf::Conf, fitness::Fitness, genome::Genome, network::Network, pop::Pop};
+use alloc::vec::Vec;
+use rand::{Rng, seq::IndexedRandom};
+
+/// Competitive coevolution between a population of hosts and a population of parasites, where
+/// every genome is scored by playing against members of the other population.
+///
+/// Each generation, the hosts play a sample of the current parasites and of the parasites' hall
+/// of fame, and the other way around. Fitness is shared by competition: beating an opponent is
+/// worth one divided by the number of genomes that beat it, so rare wins count for more. The
+/// champions of both populations are then added to their hall of fame, and both populations
+/// evolve as usual.
+#[derive(Debug)]
+pub struct Coevo<'a, const I: usize, const O: usize> {
+    pub hosts: Pop<'a, I, O>,
+    pub parasites: Pop<'a, I, O>,
+    pub host_hall: Vec<Genome<'a, I, O>>,
+    pub parasite_hall: Vec<Genome<'a, I, O>>,
+    /// Number of opponents sampled from the current generation of the other population.
+    pub opponents: usize,
+    /// Number of opponents sampled from the hall of fame of the other population.
+    pub hall_opponents: usize,
+}
+
+impl<'a, const I: usize, const O: usize> Coevo<'a, I, O> {
+    pub fn new(conf: Conf) -> Self {
+        Self {
+            hosts: Pop::new(conf.clone()),
+            parasites: Pop::new(Conf { seed: !conf.seed, ..conf }),
+            host_hall: Vec::new(),
+            parasite_hall: Vec::new(),
+            opponents: 8,
+            hall_opponents: 4,
+        }
+    }
+
+    /// Runs a single generation. `play` is given a host and a parasite, in that order, and
+    /// returns their scores in the same order; whoever scores strictly higher wins.
+    pub fn step(&mut self, play: impl Fn(&Network<I, O>, &Network<I, O>) -> (f32, f32)) {
+        let rng = &mut self.hosts.rng();
+        let hosts = self.hosts.genomes.iter().map(Network::from).collect::<Vec<_>>();
+        let parasites = self.parasites.genomes.iter().map(Network::from).collect::<Vec<_>>();
+
+        let host_opponents = self.sample(&parasites, &self.parasite_hall, rng);
+        let parasite_opponents = self.sample(&hosts, &self.host_hall, rng);
+
+        let host_fitness = share(&hosts, &host_opponents, |host, parasite| {
+            let (host, parasite) = play(host, parasite);
+            host > parasite
+        });
+
+        let parasite_fitness = share(&parasites, &parasite_opponents, |parasite, host| {
+            let (host, parasite) = play(host, parasite);
+            parasite > host
+        });
+
+        for (pop, hall, fitness) in [
+            (&mut self.hosts, &mut self.host_hall, host_fitness),
+            (&mut self.parasites, &mut self.parasite_hall, parasite_fitness),
+        ] {
+            for (genome, fitness) in pop.genomes.iter_mut().zip(fitness) {
+                genome.fitness = fitness;
+            }
+
+            hall.extend(pop.champion().cloned());
+            pop.evolve();
+        }
+    }
+
+    fn sample(&self, current: &[Network<I, O>], hall: &[Genome<'a, I, O>], rng: &mut impl Rng) -> Vec<Network<I, O>> {
+        current.choose_multiple(rng, self.opponents).cloned()
+            .chain(hall.choose_multiple(rng, self.hall_opponents).map(Network::from))
+            .collect()
+    }
+}
+
+// a win against an opponent that `n` players beat is worth `1 / n`
+pub(crate) fn share<const I: usize, const O: usize>(
+    players: &[Network<I, O>],
+    opponents: &[Network<I, O>],
+    beats: impl Fn(&Network<I, O>, &Network<I, O>) -> bool,
+) -> Vec<Fitness> {
+    let wins = players.iter()
+        .map(|player| opponents.iter().map(|opponent| beats(player, opponent)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let beaten = (0..opponents.len())
+        .map(|opponent| wins.iter().filter(|wins| wins[opponent]).count())
+        .collect::<Vec<_>>();
+
+    wins.iter().map(|wins| {
+        let shared = wins.iter().zip(&beaten)
+            .filter(|(win, _)| **win)
+            .map(|(_, beaten)| 1.0 / (*beaten as f32))
+            .sum::<f32>();
+
+        Fitness::from(shared)
+    }).collect()
+}
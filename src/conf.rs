@@ -0,0 +1,72 @@
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Conf {
+    /// Seed that every random number drawn by a population is derived from.
+    pub seed: u64,
+    pub pop_size: usize,
+    pub excess_coeff: f32,
+    pub disjoint_coeff: f32,
+    pub weight_coeff: f32,
+    /// Compatibility distance below which a genome joins a species.
+    pub compat_threshold: f32,
+    /// Share of each species, fittest first, that is allowed to reproduce.
+    pub survival_threshold: f32,
+    /// Number of the fittest genomes of each species that are copied into the next generation
+    /// unchanged.
+    pub elitism: usize,
+    /// Generations a species may go without improving its best fitness before it is removed.
+    pub max_stagnation: usize,
+    /// Number of calls to `Pop::replace` that a genome must survive before it may be replaced.
+    pub min_age: usize,
+    pub add_edge_prob: f64,
+    pub split_edge_prob: f64,
+    pub weight_prob: f64,
+    pub tau_prob: f64,
+    pub activation_prob: f64,
+    /// Largest amount that `mutate_weight` nudges a weight by in either direction.
+    pub weight_mutate_power: f32,
+    /// Chance that `mutate_weight` draws a new weight for an edge rather than nudging it.
+    pub weight_replace_prob: f64,
+    /// Largest magnitude that `mutate_weight` will leave a weight with.
+    pub weight_max: f32,
+    /// Allows [`Genome::mutate_add_edge`](crate::genome::Genome::mutate_add_edge) to close cycles,
+    /// including self-loops. Genomes evolved this way must be evaluated with a
+    /// [`RecurrentNetwork`](crate::network::RecurrentNetwork).
+    pub recurrent: bool,
+    /// How many tail and head pairs `mutate_add_edge` may draw before giving up when every pair
+    /// it drew would have closed a cycle in a feed-forward genome.
+    pub add_edge_attempts: usize,
+    /// Largest amount that `mutate_tau` nudges a time constant by in either direction.
+    pub tau_mutate_power: f32,
+    /// Smallest time constant that `mutate_tau` will leave a node with.
+    pub tau_min: f32,
+}
+
+impl Default for Conf {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            pop_size: 150,
+            excess_coeff: 1.0,
+            disjoint_coeff: 1.0,
+            weight_coeff: 0.4,
+            compat_threshold: 3.0,
+            survival_threshold: 0.2,
+            elitism: 1,
+            max_stagnation: 15,
+            min_age: 20,
+            add_edge_prob: 0.05,
+            split_edge_prob: 0.03,
+            weight_prob: 0.8,
+            tau_prob: 0.0,
+            activation_prob: 0.0,
+            weight_mutate_power: 0.5,
+            weight_replace_prob: 0.1,
+            weight_max: 30.0,
+            recurrent: false,
+            add_edge_attempts: 20,
+            tau_mutate_power: 0.1,
+            tau_min: 0.01,
+        }
+    }
+}
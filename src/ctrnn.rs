@@ -0,0 +1,57 @@
+extern crate alloc;
+use crate::{genome::Genome, network::Network};
+use core::array;
+use alloc::{boxed::Box, vec, vec::Vec};
+
+/// A continuous-time recurrent interpretation of a genome.
+///
+/// Every hidden and output node holds a state `y` that follows
+/// `dy/dt = (-y + f(bias + response * aggreg(w * x))) / tau`, where `tau` is the node's evolvable
+/// time constant. The states are integrated with the forward Euler method, updating all of them
+/// at once from the states of the previous step.
+#[derive(Clone, Debug)]
+pub struct Ctrnn<const I: usize, const O: usize> {
+    network: Network<I, O>,
+    values: Box<[f32]>,
+}
+
+impl<const I: usize, const O: usize> Ctrnn<I, O> {
+    /// Holds `inputs` constant while taking `steps` Euler steps of size `dt`, returning the
+    /// output states afterwards.
+    pub fn advance(&mut self, inputs: [f32; I], dt: f32, steps: usize) -> [f32; O] {
+        let network = &self.network;
+
+        for (value, (input, bias)) in self.values.iter_mut().zip(inputs.iter().zip(&network.biases)) {
+            *value = bias + input;
+        }
+
+        let mut next = self.values.to_vec();
+        let mut weighted = Vec::new();
+
+        for _ in 0..steps {
+            for (idx, neuron) in network.neurons.iter().enumerate() {
+                weighted.clear();
+                weighted.extend(network.conns[neuron.conns.clone()].iter().map(|conn| conn.weight * self.values[conn.tail]));
+
+                let curr = self.values[I + idx];
+                next[I + idx] = curr + dt * (neuron.eval(&weighted) - curr) / neuron.tau;
+            }
+
+            self.values.copy_from_slice(&next);
+        }
+
+        array::from_fn::<_, O, _>(|idx| self.values[network.outputs[idx]])
+    }
+
+    pub fn reset(&mut self) {
+        self.values.fill(0.0);
+    }
+}
+
+impl<const I: usize, const O: usize> From<&Genome<'_, I, O>> for Ctrnn<I, O> {
+    fn from(genome: &Genome<'_, I, O>) -> Self {
+        let network = Network::from(genome);
+        let values = vec![0.0; I + network.neurons.len()].into_boxed_slice();
+        Self { network, values }
+    }
+}
@@ -0,0 +1,143 @@
+extern crate alloc;
+use crate::{edge::Edge, genome::Genome, repr::*};
+use core::fmt;
+use alloc::vec::Vec;
+use hashbrown::HashMap;
+
+/// Edges that only one of two genomes has.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Unmatched {
+    pub lhs: Vec<EdgeGene>,
+    pub rhs: Vec<EdgeGene>,
+}
+
+/// How two genomes differ, comparing their edges and nodes by innovation number.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Diff {
+    /// Edges that both genomes have, as they are in the left and in the right genome.
+    pub matching: Vec<(EdgeGene, EdgeGene)>,
+    /// Edges that only one genome has, with an innovation number no larger than the largest
+    /// of the other genome.
+    pub disjoint: Unmatched,
+    /// Edges that only one genome has, with an innovation number larger than every one of the
+    /// other genome.
+    pub excess: Unmatched,
+    /// Nodes that only the right genome has.
+    pub added: Vec<NodeGene>,
+    /// Nodes that only the left genome has.
+    pub removed: Vec<NodeGene>,
+    /// Nodes that both genomes have but whose bias, response, tau, activation or aggregation
+    /// differ, as they are in the left and in the right genome.
+    pub changed: Vec<(NodeGene, NodeGene)>,
+}
+
+impl Diff {
+    /// Returns how much the weight of every matching edge changed from the left genome to the
+    /// right one, by innovation number.
+    pub fn weight_deltas(&self) -> impl Iterator<Item = (usize, f32)> + '_ {
+        self.matching.iter().map(|(lhs, rhs)| (lhs.innov, rhs.weight - lhs.weight))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matching.iter().all(|(lhs, rhs)| lhs == rhs)
+            && self.disjoint == Unmatched::default()
+            && self.excess == Unmatched::default()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+impl<const I: usize, const O: usize> Genome<'_, I, O> {
+    /// Compares this genome, on the left, with `other`, on the right.
+    pub fn diff(&self, other: &Genome<'_, I, O>) -> Diff {
+        // the edges come in innovation order, and the genes are kept in the same order
+        let matching = self.edges.innov_matching(&other.edges).into_iter()
+            .map(|(lhs, rhs)| (EdgeGene::from(lhs), EdgeGene::from(rhs)))
+            .collect::<Vec<_>>();
+
+        let genes = |edges: Vec<&Edge>| edges.into_iter().map(EdgeGene::from).collect::<Vec<_>>();
+        let (lhs, rhs) = (&self.edges, &other.edges);
+
+        let disjoint = Unmatched { lhs: genes(lhs.innov_disjoint(rhs)), rhs: genes(rhs.innov_disjoint(lhs)) };
+        let excess = Unmatched { lhs: genes(lhs.innov_excess(rhs)), rhs: genes(rhs.innov_excess(lhs)) };
+
+        let lhs_nodes = GenomeRepr::from(self).nodes;
+        let rhs_nodes = GenomeRepr::from(other).nodes;
+
+        let lhs = lhs_nodes.iter().map(|node| (node.innov, node)).collect::<HashMap<_, _>>();
+        let rhs = rhs_nodes.iter().map(|node| (node.innov, node)).collect::<HashMap<_, _>>();
+
+        Diff {
+            matching,
+            disjoint,
+            excess,
+            added: rhs_nodes.iter().filter(|node| !lhs.contains_key(&node.innov)).cloned().collect(),
+            removed: lhs_nodes.iter().filter(|node| !rhs.contains_key(&node.innov)).cloned().collect(),
+            changed: lhs_nodes.iter()
+                .filter_map(|node| Some((node.clone(), (*rhs.get(&node.innov)?).clone())))
+                .filter(|(lhs, rhs)| lhs != rhs)
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let edge = |f: &mut fmt::Formatter<'_>, sign: char, edge: &EdgeGene| {
+            writeln!(f, "  {sign} edge {} ({} -> {}) weight {:.3}{}", edge.innov, edge.tail, edge.head, edge.weight, match edge.enabled { false => ", disabled", true => "" })
+        };
+
+        let node = |f: &mut fmt::Formatter<'_>, sign: char, node: &NodeGene| {
+            writeln!(f, "  {sign} node {} bias {:.3}, {}, {}", node.innov, node.bias, node.activation, node.aggregation)
+        };
+
+        writeln!(f, "{} matching edges", self.matching.len())?;
+
+        for (lhs, rhs) in self.matching.iter().filter(|(lhs, rhs)| lhs != rhs) {
+            write!(f, "  ~ edge {} ({} -> {})", lhs.innov, lhs.tail, lhs.head)?;
+
+            if lhs.weight != rhs.weight {
+                write!(f, " weight {:.3} -> {:.3} ({:+.3})", lhs.weight, rhs.weight, rhs.weight - lhs.weight)?;
+            }
+
+            if lhs.enabled != rhs.enabled {
+                write!(f, " {}", match rhs.enabled { false => "disabled", true => "enabled" })?;
+            }
+
+            writeln!(f)?;
+        }
+
+        for (name, unmatched) in [("disjoint", &self.disjoint), ("excess", &self.excess)] {
+            writeln!(f, "{} {name} edges", unmatched.lhs.len() + unmatched.rhs.len())?;
+            unmatched.lhs.iter().try_for_each(|gene| edge(f, '-', gene))?;
+            unmatched.rhs.iter().try_for_each(|gene| edge(f, '+', gene))?;
+        }
+
+        writeln!(f, "{} added and {} removed nodes", self.added.len(), self.removed.len())?;
+        self.removed.iter().try_for_each(|gene| node(f, '-', gene))?;
+        self.added.iter().try_for_each(|gene| node(f, '+', gene))?;
+
+        writeln!(f, "{} changed nodes", self.changed.len())?;
+
+        for (lhs, rhs) in self.changed.iter() {
+            write!(f, "  ~ node {}", lhs.innov)?;
+
+            for (name, lhs, rhs) in [("bias", lhs.bias, rhs.bias), ("response", lhs.response, rhs.response), ("tau", lhs.tau, rhs.tau)] {
+                if lhs != rhs {
+                    write!(f, " {name} {lhs:.3} -> {rhs:.3}")?;
+                }
+            }
+
+            for (lhs, rhs) in [(&lhs.activation, &rhs.activation), (&lhs.aggregation, &rhs.aggregation)] {
+                if lhs != rhs {
+                    write!(f, " {lhs} -> {rhs}")?;
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
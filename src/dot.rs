@@ -0,0 +1,56 @@
+extern crate alloc;
+use crate::{activ, genome::Genome, node::*};
+use core::fmt;
+use alloc::vec::Vec;
+
+impl<const I: usize, const O: usize> Genome<'_, I, O> {
+    /// Formats the genome as a Graphviz digraph.
+    ///
+    /// Nodes that share a layer are ranked together and labelled with their innovation number,
+    /// bias and activation function. Edges are green when their weight is positive and red when
+    /// it's negative, get thicker the larger their weight, and are dashed when disabled.
+    pub fn dot(&self) -> impl fmt::Display + '_ {
+        fmt::from_fn(move |f| {
+            let mut nodes = self.inputs.iter()
+                .map(|input| (0, input.innov(), input.bias(), "input", "box"))
+                .chain(self.hiddens.iter().map(|hidden| (hidden.layer(), hidden.innov(), hidden.bias(), name(hidden), "circle")))
+                .chain(self.outputs.iter().map(|output| (output.layer(), output.innov(), output.bias(), name(output), "doublecircle")))
+                .collect::<Vec<_>>();
+
+            nodes.sort_by_key(|(layer, innov, ..)| (*layer, *innov));
+
+            writeln!(f, "digraph genome {{")?;
+            writeln!(f, "    rankdir=LR;")?;
+
+            for rank in nodes.chunk_by(|lhs, rhs| lhs.0 == rhs.0) {
+                write!(f, "    {{ rank=same;")?;
+
+                for (_, innov, bias, activ, shape) in rank {
+                    write!(f, " {innov} [shape={shape}, label=\"{innov}\\nbias {bias:.3}\\n{activ}\"];")?;
+                }
+
+                writeln!(f, " }}")?;
+            }
+
+            for edge in self.edges.iter() {
+                let color = match edge.weight < 0.0 { false => "forestgreen", true => "firebrick" };
+                let width = 0.5 + f32::min(edge.weight.abs(), 5.0);
+                let style = match edge.enabled.get() { false => "dashed", true => "solid" };
+
+                writeln!(
+                    f,
+                    "    {} -> {} [label=\"{:.3}\", color={color}, penwidth={width:.2}, style={style}];",
+                    edge.tail.innov(),
+                    edge.head.innov(),
+                    edge.weight,
+                )?;
+            }
+
+            writeln!(f, "}}")
+        })
+    }
+}
+
+pub(crate) fn name(node: &impl Node) -> &'static str {
+    activ::activation_name(node.activation()).unwrap_or("?")
+}
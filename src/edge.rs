@@ -1,7 +1,8 @@
 extern crate alloc;
-use crate::{node::*, pop::Pop};
-use core::{cell::Cell, convert::Into, fmt, slice};
-use alloc::collections::btree_set::BTreeSet;
+use crate::{node::*, pop::Innov, validate::Violation};
+use core::{cell::Cell, cmp::Ordering, convert::Into, fmt, mem, ptr};
+use alloc::{collections::btree_set::{self, BTreeSet}, vec, vec::Vec};
+use hashbrown::{HashMap, HashSet};
 
 #[derive(Clone, PartialEq)]
 pub struct Edge<'a> {
@@ -11,6 +12,9 @@ pub struct Edge<'a> {
     pub enabled: Cell<bool>,
     pub layer: usize,
     pub innov: usize,
+    /// Whether the edge feeds back into a node that its tail depends on, and so carries the value
+    /// its tail had at the end of the previous step.
+    pub recurrent: bool,
 }
 
 impl<'a> Edge<'a> {
@@ -23,10 +27,28 @@ impl<'a> Edge<'a> {
         head.update_layer(tail.layer() + 1);
 
         Self {
-            innov: Pop::next_edge_innov(&tail, &head),
+            innov: Innov::next_edge_innov(&tail, &head),
             layer: tail.layer(),
             enabled: Cell::new(true),
             weight: 1.0,
+            recurrent: false,
+            tail,
+            head,
+        }
+    }
+
+    // unlike `new`, the layer of the head is left alone since a node can never be placed after
+    // every node that it feeds back into
+    pub fn recurrent(tail: impl Into<Tail<'a>>, head: impl Into<Head<'a>>) -> Self {
+        let tail: Tail = tail.into();
+        let head: Head = head.into();
+
+        Self {
+            innov: Innov::next_edge_innov(&tail, &head),
+            layer: tail.layer(),
+            enabled: Cell::new(true),
+            weight: 1.0,
+            recurrent: true,
             tail,
             head,
         }
@@ -35,6 +57,19 @@ impl<'a> Edge<'a> {
 
 impl Eq for Edge<'_> {}
 
+// a genome has at most one edge per innovation number, so its edges are kept in innovation order
+impl Ord for Edge<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.innov.cmp(&other.innov)
+    }
+}
+
+impl PartialOrd for Edge<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl fmt::Debug for Edge<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f
@@ -45,6 +80,7 @@ impl fmt::Debug for Edge<'_> {
             .field("enabled", &self.enabled.get())
             .field("layer", &self.layer)
             .field("innov", &self.innov)
+            .field("recurrent", &self.recurrent)
             .finish()
     }
 }
@@ -59,24 +95,109 @@ impl Edges<'_> {
 }
 
 impl<'a> Edges<'a> {
-    pub fn get(&self, edge: &Edge<'_>) -> Option<&Edge<'_>> {
+    pub fn get(&self, edge: &Edge<'a>) -> Option<&Edge<'a>> {
         self.0.get(edge)
     }
 
-    pub fn insert(&mut self, edge: Edge<'_>) {
+    pub fn insert(&mut self, edge: Edge<'a>) {
         assert!(self.0.insert(edge));
     }
 
-    pub fn iter(&self) -> slice::Iter<'_, Edge<'a>> {
-        todo!()
+    /// Iterates over the edges in innovation order.
+    pub fn iter(&self) -> btree_set::Iter<'_, Edge<'a>> {
+        self.0.iter()
+    }
+
+    /// Replaces the weight of every edge with whatever `f` makes of it.
+    pub fn update_weights(&mut self, mut f: impl FnMut(f32) -> f32) {
+        // the weights aren't part of the order, so the edges can be put back as they were
+        self.0 = mem::take(&mut self.0).into_iter()
+            .map(|edge| Edge { weight: f(edge.weight), ..edge })
+            .collect();
     }
 
-    pub fn innov_matching() {
-        todo!()
+    pub fn find(&self, tail: &Tail, head: &Head) -> Option<&Edge<'a>> {
+        self.iter().find(|edge| edge.tail == *tail && edge.head == *head)
+    }
+
+    /// Returns true if `tail` can be reached by following forward edges out of `head`, in which
+    /// case an edge from `tail` to `head` would close a cycle.
+    pub fn reaches(&self, head: &Head, tail: &Tail) -> bool {
+        // only hidden nodes are ever both the head and the tail of an edge
+        let (Some(head), Some(tail)) = (head.hidden(), tail.hidden()) else {
+            return false;
+        };
+
+        let mut stack = vec![head];
+        let mut seen = HashSet::new();
+
+        while let Some(curr) = stack.pop() {
+            if ptr::eq(curr, tail) {
+                return true;
+            }
+
+            if seen.insert(curr.innov()) {
+                stack.extend(self.iter()
+                    .filter(|edge| !edge.recurrent)
+                    .filter(|edge| edge.tail.hidden().is_some_and(|tail| ptr::eq(tail, curr)))
+                    .filter_map(|edge| edge.head.hidden()));
+            }
+        }
+
+        false
+    }
+
+    /// Pushes the head of every forward edge past its tail until every forward edge runs to a
+    /// later layer, since raising the layer of a node leaves the nodes that it feeds into behind.
+    ///
+    /// Gives up on forward edges that close a cycle, since their layers would never settle, and
+    /// returns one of the edges that's still out of place.
+    pub fn settle_layers(&self) -> Result<(), Violation> {
+        let unsettled = |edge: &&Edge| !edge.recurrent && edge.head.layer() <= edge.tail.layer();
+
+        // every pass settles the next edge along each path, and an acyclic path can't have more
+        // edges than there are in total
+        for _ in 0..=self.len() {
+            let mut moved = false;
+
+            for edge in self.iter().filter(unsettled) {
+                edge.head.update_layer(edge.tail.layer() + 1);
+                moved = true;
+            }
+
+            if !moved {
+                return Ok(());
+            }
+        }
+
+        match self.iter().find(unsettled) {
+            Some(edge) => Err(Violation::Cycle(edge.innov)),
+            None => Ok(()),
+        }
+    }
+
+    /// Pairs up every edge with the edge of `other` that has the same innovation number.
+    pub fn innov_matching<'b>(&'b self, other: &'b Edges<'b>) -> Vec<(&'b Edge<'a>, &'b Edge<'b>)> {
+        let other = other.iter().map(|edge| (edge.innov, edge)).collect::<HashMap<_, _>>();
+        self.iter().filter_map(|edge| Some((edge, *other.get(&edge.innov)?))).collect()
+    }
+
+    /// Returns every edge whose innovation number `other` doesn't have, but that's no larger than
+    /// the largest innovation number of `other`.
+    pub fn innov_disjoint(&self, other: &Edges<'_>) -> Vec<&Edge<'a>> {
+        let Some(max) = other.0.last().map(|edge| edge.innov) else {
+            return Vec::new();
+        };
+
+        let other = other.iter().map(|edge| edge.innov).collect::<HashSet<_>>();
+        self.iter().filter(|edge| edge.innov <= max && !other.contains(&edge.innov)).collect()
     }
 
-    pub fn innov_disjoint() {
-        todo!()
+    /// Returns every edge whose innovation number is larger than every one of `other`, which is
+    /// every edge if `other` has none.
+    pub fn innov_excess(&self, other: &Edges<'_>) -> Vec<&Edge<'a>> {
+        let max = other.0.last().map(|edge| edge.innov);
+        self.iter().filter(|edge| max.is_none_or(|max| edge.innov > max)).collect()
     }
 }
 
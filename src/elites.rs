@@ -0,0 +1,123 @@
+extern crate alloc;
+use crate::{conf::Conf, fitness::Fitness, genome::Genome, lineage::Lineage};
+use alloc::{collections::btree_map::{BTreeMap, Entry}, vec, vec::Vec};
+use rand::{Rng, seq::IteratorRandom};
+
+/// A MAP-Elites archive: a grid over `D` user defined behavior descriptors where every cell keeps
+/// the fittest genome found so far whose behavior falls into it.
+#[derive(Debug)]
+pub struct Elites<'a, const I: usize, const O: usize, const D: usize> {
+    cells: BTreeMap<[usize; D], Genome<'a, I, O>>,
+    pub conf: Conf,
+    /// Number of cells along each descriptor.
+    pub bins: [usize; D],
+    /// Range of each descriptor, with values outside of it falling into the outermost cells.
+    pub bounds: [(f32, f32); D],
+    /// Chance that a child is the crossover of two elites rather than a mutated copy of one.
+    pub crossover_prob: f64,
+    /// Number of calls to [`Elites::step`] so far, which the children of each call are born in.
+    pub steps: usize,
+    pub(crate) next_genome: usize,
+}
+
+impl<'a, const I: usize, const O: usize, const D: usize> Elites<'a, I, O, D> {
+    pub fn new(conf: Conf, bins: [usize; D], bounds: [(f32, f32); D]) -> Self {
+        assert!(bins.iter().all(|bins| *bins > 0));
+
+        Self {
+            cells: BTreeMap::new(),
+            conf,
+            bins,
+            bounds,
+            crossover_prob: 0.0,
+            steps: 0,
+            next_genome: 0,
+        }
+    }
+
+    pub fn cell(&self, descriptor: [f32; D]) -> [usize; D] {
+        let mut cell = [0; D];
+
+        for (idx, cell) in cell.iter_mut().enumerate() {
+            let (min, max) = self.bounds[idx];
+            let norm = (descriptor[idx] - min) / (max - min);
+            *cell = ((norm * self.bins[idx] as f32) as usize).min(self.bins[idx] - 1);
+        }
+
+        cell
+    }
+
+    pub fn get(&self, cell: [usize; D]) -> Option<&Genome<'a, I, O>> {
+        self.cells.get(&cell)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&[usize; D], &Genome<'a, I, O>)> {
+        self.cells.iter()
+    }
+
+    /// Places `genome` into the cell of `descriptor` if the cell is empty or its elite is less
+    /// fit, returning whether it was placed.
+    pub fn insert(&mut self, genome: Genome<'a, I, O>, descriptor: [f32; D]) -> bool {
+        match self.cells.entry(self.cell(descriptor)) {
+            Entry::Vacant(entry) => {
+                entry.insert(genome);
+                true
+            }
+            Entry::Occupied(mut entry) if genome.fitness > entry.get().fitness => {
+                entry.insert(genome);
+                true
+            }
+            Entry::Occupied(_) => false,
+        }
+    }
+
+    /// Breeds `batch` children from randomly selected elites, or from fresh genomes while the
+    /// archive is still empty, and offers each of them to the archive. Returns how many were
+    /// placed.
+    pub fn step(
+        &mut self,
+        batch: usize,
+        rng: &mut impl Rng,
+        eval: impl Fn(&Genome<'a, I, O>) -> (Fitness, [f32; D]),
+    ) -> usize {
+        let mut placed = 0;
+
+        for _ in 0..batch {
+            let (mut child, parents) = match self.cells.values().choose(rng) {
+                None => (Genome::default(), Vec::new()),
+                Some(lhs) if rng.random_bool(self.crossover_prob) => {
+                    let rhs = self.cells.values().choose(rng).unwrap();
+                    (Genome::crossover(lhs, rhs, rng), vec![lhs.lineage.id, rhs.lineage.id])
+                }
+                Some(elite) => (elite.clone(), vec![elite.lineage.id]),
+            };
+
+            child.lineage = Lineage::new(self.next_genome, parents, self.steps);
+            self.next_genome += 1;
+
+            child.mutate(&self.conf, rng);
+
+            let (fitness, descriptor) = eval(&child);
+            child.fitness = fitness;
+
+            placed += self.insert(child, descriptor) as usize;
+        }
+
+        self.steps += 1;
+        placed
+    }
+
+    /// Share of the cells that hold an elite.
+    pub fn coverage(&self) -> f32 {
+        self.cells.len() as f32 / self.bins.iter().product::<usize>() as f32
+    }
+
+    /// Sum of the fitness of every elite.
+    pub fn qd_score(&self) -> f32 {
+        self.cells.values().map(|genome| *genome.fitness).sum()
+    }
+
+    pub fn champion(&self) -> Option<&Genome<'a, I, O>> {
+        self.cells.values().max_by(|lhs, rhs| lhs.fitness.total_cmp(&rhs.fitness))
+    }
+}
@@ -0,0 +1,249 @@
+extern crate alloc;
+use crate::{genome::Genome, hyper::*, network::*};
+use core::array;
+use alloc::{collections::VecDeque, vec, vec::Vec};
+use hashbrown::{HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Point {
+    Input(usize),
+    Hidden(usize),
+    Output(usize),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Quad {
+    center: [f32; 2],
+    width: f32,
+    depth: usize,
+    weight: f32,
+    children: Option<[usize; 4]>,
+}
+
+impl Quad {
+    fn new(center: [f32; 2], width: f32, depth: usize) -> Self {
+        Self { center, width, depth, weight: 0.0, children: None }
+    }
+}
+
+/// An ES-HyperNEAT substrate that decides where its hidden nodes go by itself.
+///
+/// Starting from every input, the square `[-1, 1]²` is recursively divided into a quadtree for as
+/// long as the weights that the CPPN paints onto it keep varying, and hidden nodes are placed at
+/// the centres of the leaves that sit inside a band of contrasting weights. The search is then
+/// repeated from the newly placed hidden nodes, and finally run backwards from every output so it
+/// can only connect to hidden nodes that already exist. Hidden nodes that don't end up on a path
+/// to an output are discarded.
+///
+/// Only the `inputs`, `outputs`, `max_weight`, `activ` and `aggreg` of the wrapped [`Substrate`]
+/// are used.
+#[derive(Clone, Debug)]
+pub struct EvolvableSubstrate<const I: usize, const O: usize> {
+    pub substrate: Substrate<I, O, 2>,
+    /// Depth that the quadtree is always divided to.
+    pub initial_depth: usize,
+    /// Depth that the quadtree is never divided past.
+    pub max_depth: usize,
+    /// Variance of the weights below a quad above which it is divided further.
+    pub division_threshold: f32,
+    /// Variance of the weights below a quad above which its children are searched for hidden
+    /// nodes instead of the quad itself.
+    pub variance_threshold: f32,
+    /// How much a quad's weight must differ from both of its neighbours along either axis for a
+    /// hidden node to be placed on it.
+    pub band_threshold: f32,
+    /// How many times the search is repeated from the hidden nodes found by the previous search.
+    pub iterations: usize,
+}
+
+impl<const I: usize, const O: usize> EvolvableSubstrate<I, O> {
+    pub fn new(inputs: [[f32; 2]; I], outputs: [[f32; 2]; O]) -> Self {
+        Self {
+            substrate: Substrate::new(inputs, outputs),
+            initial_depth: 2,
+            max_depth: 3,
+            division_threshold: 0.5,
+            variance_threshold: 0.03,
+            band_threshold: 0.3,
+            iterations: 1,
+        }
+    }
+
+    /// Places the hidden nodes and returns the resulting network.
+    ///
+    /// Hidden nodes are ordered by when they were found. Connections from a hidden node to one
+    /// that was found before it are dropped so that the network stays feed-forward.
+    pub fn build<const C: usize, const K: usize>(&self, cppn: &Genome<'_, C, K>) -> Network<I, O> {
+        let cppn = Cppn::from(cppn);
+
+        let mut hiddens = Vec::<[f32; 2]>::new();
+        let mut index = HashMap::<[u32; 2], usize>::new();
+        let mut links = Vec::<(Point, Point, f32)>::new();
+
+        let mut place = |point: [f32; 2], hiddens: &mut Vec<_>| *index.entry(point.map(f32::to_bits)).or_insert_with(|| {
+            hiddens.push(point);
+            hiddens.len() - 1
+        });
+
+        for (idx, input) in self.substrate.inputs.iter().enumerate() {
+            for (point, weight) in self.search(&cppn, *input, true) {
+                links.push((Point::Input(idx), Point::Hidden(place(point, &mut hiddens)), weight));
+            }
+        }
+
+        let mut frontier = 0..hiddens.len();
+
+        for _ in 0..self.iterations {
+            let start = hiddens.len();
+
+            for tail in frontier {
+                for (point, weight) in self.search(&cppn, hiddens[tail], true) {
+                    let head = place(point, &mut hiddens);
+                    links.push((Point::Hidden(tail), Point::Hidden(head), weight));
+                }
+            }
+
+            frontier = start..hiddens.len();
+        }
+
+        for (idx, output) in self.substrate.outputs.iter().enumerate() {
+            for (point, weight) in self.search(&cppn, *output, false) {
+                if let Some(&tail) = index.get(&point.map(f32::to_bits)) {
+                    links.push((Point::Hidden(tail), Point::Output(idx), weight));
+                }
+            }
+        }
+
+        links.retain(|link| !matches!(link, (Point::Hidden(tail), Point::Hidden(head), _) if head <= tail));
+
+        // walk backwards from the outputs to find every hidden node that contributes to them
+        let mut kept = HashSet::new();
+        let mut stack = links.iter()
+            .filter(|(_, head, _)| matches!(head, Point::Output(_)))
+            .map(|(tail, ..)| *tail)
+            .collect::<Vec<_>>();
+
+        while let Some(point) = stack.pop() {
+            if let Point::Hidden(hidden) = point {
+                if kept.insert(hidden) {
+                    stack.extend(links.iter().filter(|(_, head, _)| *head == point).map(|(tail, ..)| *tail));
+                }
+            }
+        }
+
+        let mut slots = vec![None; hiddens.len()];
+        let mut next = I;
+
+        for (hidden, slot) in slots.iter_mut().enumerate().filter(|(hidden, _)| kept.contains(hidden)) {
+            *slot = Some(next);
+            next += 1;
+        }
+
+        let slot = |point: Point| match point {
+            Point::Input(input) => Some(input),
+            Point::Hidden(hidden) => slots[hidden],
+            Point::Output(output) => Some(next + output),
+        };
+
+        let heads = (0..hiddens.len()).filter(|hidden| kept.contains(hidden)).map(Point::Hidden)
+            .chain((0..O).map(Point::Output));
+
+        let mut neurons = Vec::new();
+        let mut conns = Vec::new();
+
+        for head in heads {
+            let first = conns.len();
+
+            conns.extend(links.iter()
+                .filter(|(_, link, _)| *link == head)
+                .filter_map(|(tail, _, weight)| Some(Conn {
+                    tail: slot(*tail)?,
+                    weight: weight * self.substrate.max_weight,
+                })));
+
+            neurons.push(self.substrate.neuron(first..conns.len()));
+        }
+
+        Network {
+            biases: [0.0; I],
+            neurons: neurons.into_boxed_slice(),
+            conns: conns.into_boxed_slice(),
+            outputs: array::from_fn::<_, O, _>(|idx| next + idx),
+        }
+    }
+
+    /// Divides the substrate around `point` into a quadtree and returns the centre and weight of
+    /// every quad that a hidden node should be connected to.
+    fn search<const C: usize, const K: usize>(&self, cppn: &Cppn<C, K>, point: [f32; 2], outgoing: bool) -> Vec<([f32; 2], f32)> {
+        let weight = |other: [f32; 2]| match outgoing {
+            false => cppn.query(other, point)[0],
+            true => cppn.query(point, other)[0],
+        };
+
+        let mut tree = vec![Quad::new([0.0, 0.0], 1.0, 1)];
+        let mut queue = VecDeque::from([0]);
+
+        while let Some(idx) = queue.pop_front() {
+            let Quad { center: [x, y], width, depth, .. } = tree[idx];
+            let half = width / 2.0;
+
+            let children = [[-1.0, -1.0], [-1.0, 1.0], [1.0, -1.0], [1.0, 1.0]].map(|[dx, dy]| {
+                let mut child = Quad::new([x + (dx * half), y + (dy * half)], half, depth + 1);
+                child.weight = weight(child.center);
+                tree.push(child);
+                tree.len() - 1
+            });
+
+            tree[idx].children = Some(children);
+
+            if depth < self.initial_depth || (depth < self.max_depth && variance(&tree, idx) > self.division_threshold) {
+                queue.extend(children);
+            }
+        }
+
+        let mut found = Vec::new();
+        self.extract(&tree, 0, &weight, &mut found);
+        found
+    }
+
+    fn extract(&self, tree: &[Quad], idx: usize, weight: &impl Fn([f32; 2]) -> f32, found: &mut Vec<([f32; 2], f32)>) {
+        let Some(children) = tree[idx].children else {
+            return;
+        };
+
+        for child in children {
+            if variance(tree, child) >= self.variance_threshold {
+                self.extract(tree, child, weight, found);
+                continue;
+            }
+
+            let Quad { center: [x, y], width, weight: curr, .. } = tree[child];
+            // a quad reaches `width` out from its center, so its neighbours are centered twice as far
+            let diff = |dx: f32, dy: f32| (curr - weight([x + (dx * 2.0 * width), y + (dy * 2.0 * width)])).abs();
+
+            let horizontal = f32::min(diff(-1.0, 0.0), diff(1.0, 0.0));
+            let vertical = f32::min(diff(0.0, -1.0), diff(0.0, 1.0));
+
+            if f32::max(horizontal, vertical) > self.band_threshold {
+                found.push(([x, y], curr));
+            }
+        }
+    }
+}
+
+// variance of the weights of every leaf below the quad at `idx`
+fn variance(tree: &[Quad], idx: usize) -> f32 {
+    let mut weights = Vec::new();
+    let mut stack = vec![idx];
+
+    while let Some(idx) = stack.pop() {
+        match tree[idx].children {
+            Some(children) => stack.extend(children),
+            None => weights.push(tree[idx].weight),
+        }
+    }
+
+    let len = weights.len() as f32;
+    let mean = weights.iter().sum::<f32>() / len;
+    weights.iter().map(|weight| (weight - mean) * (weight - mean)).sum::<f32>() / len
+}
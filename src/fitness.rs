@@ -1,5 +1,7 @@
+extern crate alloc;
 use crate::genome::Genome;
-use core::{mem, ops::Deref};
+use core::{cmp::Ordering, mem, ops::Deref};
+use alloc::{vec, vec::Vec};
 use rand::Rng;
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
@@ -25,6 +27,10 @@ impl Fitness {
 
         match choice { false => lhs, true => rhs }
     }
+
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
 }
 
 impl Deref for Fitness {
@@ -41,3 +47,125 @@ impl From<f32> for Fitness {
     }
 }
 
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Goal {
+    Maximize,
+    Minimize,
+}
+
+/// The scores of a genome on several objectives at once, such as its score on a task and the
+/// size of its network.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Objectives<const N: usize>(pub [f32; N]);
+
+impl<const N: usize> Objectives<N> {
+    /// Returns true if `self` is at least as good as `other` on every objective and strictly
+    /// better on at least one.
+    pub fn dominates(&self, other: &Self, goals: &[Goal; N]) -> bool {
+        let mut better = false;
+
+        for ((lhs, rhs), goal) in self.0.iter().zip(other.0.iter()).zip(goals) {
+            let (lhs, rhs) = match goal {
+                Goal::Maximize => (lhs, rhs),
+                Goal::Minimize => (rhs, lhs),
+            };
+
+            if lhs < rhs {
+                return false;
+            }
+
+            better |= lhs > rhs;
+        }
+
+        better
+    }
+
+    /// Sorts `points` into successive non-dominated fronts, returning the indices of the points
+    /// in each front, best front first.
+    pub fn fronts(points: &[Self], goals: &[Goal; N]) -> Vec<Vec<usize>> {
+        let mut dominated = vec![Vec::new(); points.len()];
+        let mut counts = vec![0; points.len()];
+
+        for (lhs, lhs_point) in points.iter().enumerate() {
+            for (rhs, rhs_point) in points.iter().enumerate() {
+                if lhs_point.dominates(rhs_point, goals) {
+                    dominated[lhs].push(rhs);
+                } else if rhs_point.dominates(lhs_point, goals) {
+                    counts[lhs] += 1;
+                }
+            }
+        }
+
+        let mut fronts = Vec::new();
+        let mut front = (0..points.len()).filter(|idx| counts[*idx] == 0).collect::<Vec<_>>();
+
+        while !front.is_empty() {
+            let mut next = Vec::new();
+
+            for idx in front.iter() {
+                for other in dominated[*idx].iter() {
+                    counts[*other] -= 1;
+
+                    if counts[*other] == 0 {
+                        next.push(*other);
+                    }
+                }
+            }
+
+            fronts.push(front);
+            front = next;
+        }
+
+        fronts
+    }
+
+    /// Returns the crowding distance of every point in `front`, in the same order. Points at the
+    /// boundary of any objective are infinitely far from the crowd.
+    pub fn crowding(points: &[Self], front: &[usize]) -> Vec<f32> {
+        let mut dists = vec![0.0; front.len()];
+        let mut order = (0..front.len()).collect::<Vec<_>>();
+
+        for objective in 0..N {
+            let value = |idx: usize| points[front[idx]].0[objective];
+            order.sort_by(|lhs, rhs| value(*lhs).total_cmp(&value(*rhs)));
+
+            let (Some(first), Some(last)) = (order.first().copied(), order.last().copied()) else {
+                continue;
+            };
+
+            let range = value(last) - value(first);
+            dists[first] = f32::INFINITY;
+            dists[last] = f32::INFINITY;
+
+            if range > 0.0 {
+                for window in order.windows(3) {
+                    dists[window[1]] += (value(window[2]) - value(window[0])) / range;
+                }
+            }
+        }
+
+        dists
+    }
+
+    /// Collapses the NSGA-II order of `points` into a fitness for each of them: a point in a
+    /// better front is always fitter, and within a front a less crowded point is fitter.
+    pub fn rank(points: &[Self], goals: &[Goal; N]) -> Vec<Fitness> {
+        let fronts = Self::fronts(points, goals);
+        let mut fitnesses = vec![Fitness::default(); points.len()];
+
+        for (rank, front) in fronts.iter().enumerate() {
+            for (idx, dist) in front.iter().zip(Self::crowding(points, front)) {
+                // squashes the crowding distance into [0, 0.5] so it never outweighs a front
+                let crowding = match dist.is_infinite() {
+                    false => 0.5 * dist / (1.0 + dist),
+                    true => 0.5,
+                };
+
+                fitnesses[*idx] = Fitness(((fronts.len() - rank) as f32) + crowding);
+            }
+        }
+
+        fitnesses
+    }
+}
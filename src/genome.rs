@@ -1,9 +1,31 @@
 extern crate alloc;
-use crate::{edge::*, fitness::Fitness, node::*};
-use core::array;
-use alloc::boxed::Box;
-use hashbrown::{HashMap, HashSet};
-use rand::{Rng, seq::IteratorRandom};
+use crate::{activ, conf::Conf, edge::*, fitness::Fitness, lineage::*, network::Network, node::*, repr::*};
+use core::{array, error, fmt};
+use alloc::{boxed::Box, vec::Vec};
+use hashbrown::HashMap;
+use rand::{Rng, seq::{IndexedRandom, IteratorRandom}};
+
+/// Why [`Genome::mutate_add_edge`] couldn't connect a pair of nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddEdgeError {
+    /// Every pair is already connected by an enabled edge, or would close a cycle in a
+    /// feed-forward genome.
+    Saturated,
+    /// Every pair drawn within `conf.add_edge_attempts` would have closed a cycle, but some pairs
+    /// were never drawn.
+    Exhausted,
+}
+
+impl fmt::Display for AddEdgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Saturated => write!(f, "no pair of nodes is left to connect"),
+            Self::Exhausted => write!(f, "every pair drawn would have closed a cycle"),
+        }
+    }
+}
+
+impl error::Error for AddEdgeError {}
 
 #[derive(Debug)]
 pub struct Genome<'a, const I: usize, const O: usize> {
@@ -12,24 +34,69 @@ pub struct Genome<'a, const I: usize, const O: usize> {
     pub hiddens: Hiddens,
     pub edges: Edges<'a>,
     pub fitness: Fitness,
+    pub age: usize,
+    /// Id of the species that the genome was bred in, if it was bred at all.
+    pub parent_species: Option<usize>,
+    pub lineage: Lineage,
 }
 
 impl<'a, const I: usize, const O: usize> Genome<'a, I, O> {
-    pub fn mutate_add_edge(&mut self, rng: &mut impl Rng) {
-        let tail = self.inputs.iter().map(Tail::from)
-            .chain(self.hiddens.iter().map(Tail::from))
-            .choose_stable(rng).unwrap();
+    /// Connects a random pair of nodes that isn't already connected by an enabled edge, either by
+    /// inserting a new edge or by re-enabling the disabled edge between them.
+    ///
+    /// Leaves the genome untouched and returns an error if no pair could be connected, telling
+    /// whether there was nothing left to connect or `conf.add_edge_attempts` ran out first.
+    pub fn mutate_add_edge(&mut self, conf: &Conf, rng: &mut impl Rng) -> Result<(), AddEdgeError> {
+        let heads = self.hiddens.iter().map(|hidden| Head::from(hidden.downgrade().upgrade()))
+            .chain(self.outputs.iter().map(|output| Head::from(output.downgrade().upgrade())));
 
-        let head = self.hiddens.iter().map(Head::from)
-            .chain(self.outputs.iter().map(Head::from))
-            .filter(|head| tail != *head) // check for ptr eq
-            .choose_stable(rng).unwrap();
+        let mut pairs = self.inputs.iter().map(|input| Tail::from(input.downgrade().upgrade()))
+            .chain(self.hiddens.iter().map(|hidden| Tail::from(hidden.downgrade().upgrade())))
+            .flat_map(|tail| heads.clone().map(move |head| (tail.clone(), head)))
+            .filter(|(tail, head)| conf.recurrent || tail != head) // check for ptr eq
+            .filter(|(tail, head)| self.edges.find(tail, head).is_none_or(|edge| !edge.enabled.get()))
+            .collect::<Vec<_>>();
+
+        for _ in 0..conf.add_edge_attempts {
+            if pairs.is_empty() {
+                break;
+            }
+
+            let (tail, head) = pairs.swap_remove(rng.random_range(0..pairs.len()));
+            let cycle = tail == head || self.edges.reaches(&head, &tail);
+
+            // layers and activation order only make sense if the genome stays acyclic
+            if cycle && !conf.recurrent {
+                continue;
+            }
+
+            if let Some(edge) = self.edges.find(&tail, &head) {
+                edge.enabled.set(true);
+                self.check(conf, Mutation::AddEdge);
+                return Ok(());
+            }
+
+            let edge = match cycle {
+                false => Edge::new(tail, head),
+                true => Edge::recurrent(tail, head),
+            };
+
+            self.edges.insert(edge);
 
-        let edge = Edge::new(tail, head);
-        self.edges.insert(edge);
+            // the head may have been pushed level with or past the nodes that it feeds. the forward
+            // edges of a sound genome never close a cycle, and `check` reports it if they do
+            _ = self.edges.settle_layers();
+            self.check(conf, Mutation::AddEdge);
+            return Ok(());
+        }
+
+        match pairs.is_empty() {
+            false => Err(AddEdgeError::Exhausted),
+            true => Err(AddEdgeError::Saturated),
+        }
     }
 
-    pub fn mutate_split_edge(&mut self, rng: &mut impl Rng) {
+    pub fn mutate_split_edge(&mut self, conf: &Conf, rng: &mut impl Rng) {
         let edge = self.edges.iter()
             .filter(|edge| edge.enabled.get())
             .choose_stable(rng).unwrap();
@@ -39,41 +106,183 @@ impl<'a, const I: usize, const O: usize> Genome<'a, I, O> {
         let (first, last) = self.hiddens.split_edge(edge);
         self.edges.insert(first);
         self.edges.insert(last);
+
+        _ = self.edges.settle_layers();
+        self.check(conf, Mutation::SplitEdge);
     }
 
-    pub fn mutate_weight(&mut self) {
-        todo!()
+    /// Applies each mutation with the probability that `conf` gives it, recording every one that
+    /// changed the genome in its lineage.
+    pub fn mutate(&mut self, conf: &Conf, rng: &mut impl Rng) {
+        if rng.random_bool(conf.add_edge_prob) && self.mutate_add_edge(conf, rng).is_ok() {
+            self.lineage.mutations.push(Mutation::AddEdge);
+        }
+
+        if rng.random_bool(conf.split_edge_prob) && self.edges.iter().any(|edge| edge.enabled.get()) {
+            self.mutate_split_edge(conf, rng);
+            self.lineage.mutations.push(Mutation::SplitEdge);
+        }
+
+        if rng.random_bool(conf.weight_prob) {
+            self.mutate_weight(conf, rng);
+            self.lineage.mutations.push(Mutation::Weight);
+        }
+
+        if rng.random_bool(conf.tau_prob) {
+            self.mutate_tau(conf, rng);
+            self.lineage.mutations.push(Mutation::Tau);
+        }
+
+        if rng.random_bool(conf.activation_prob) {
+            self.mutate_activation(conf, rng);
+            self.lineage.mutations.push(Mutation::Activation);
+        }
     }
 
-    pub fn activate(&self, inputs: [f32; I]) -> [f32; O] {
-        let mut map = HashMap::new();
+    // every mutation operator ends here, so that debug builds catch a genome going bad at the
+    // operator that broke it
+    fn check(&self, conf: &Conf, mutation: Mutation) {
+        if cfg!(debug_assertions) {
+            let violations = self.validate(conf);
+            assert!(violations.is_empty(), "genome {} went bad after {mutation}: {violations:?}", self.lineage.id);
+        }
+    }
+
+    pub fn mutate_tau(&mut self, conf: &Conf, rng: &mut impl Rng) {
+        let node = self.hiddens.iter().map(Head::from)
+            .chain(self.outputs.iter().map(Head::from))
+            .choose_stable(rng).unwrap();
+
+        let delta = rng.random_range(-conf.tau_mutate_power..=conf.tau_mutate_power);
+        node.update_tau(f32::max(node.tau() + delta, conf.tau_min));
+        self.check(conf, Mutation::Tau);
+    }
+
+    /// Gives a random hidden or output node a random activation function out of
+    /// [`ACTIVATIONS`](activ::ACTIVATIONS), which is how a CPPN picks up its patterns.
+    pub fn mutate_activation(&mut self, conf: &Conf, rng: &mut impl Rng) {
+        let node = self.hiddens.iter().map(Head::from)
+            .chain(self.outputs.iter().map(Head::from))
+            .choose_stable(rng).unwrap();
+
+        let (_, activation) = activ::ACTIVATIONS.choose(rng).unwrap();
+        node.update_activation(*activation);
+        self.check(conf, Mutation::Activation);
+    }
 
-        // needs to iter in ordered
-        for edge in self.edges.iter().take_while(|edge| edge.enabled.get()) {
-            let eval = match edge.tail {
-                Tail::Input(input) => input.eval(edge.weight, inputs),
-                Tail::Hidden(hidden) => hidden.eval(edge.weight, &mut map),
+    /// Nudges the weight of every edge by up to `conf.weight_mutate_power` in either direction,
+    /// or with a chance of `conf.weight_replace_prob` draws it anew.
+    pub fn mutate_weight(&mut self, conf: &Conf, rng: &mut impl Rng) {
+        self.edges.update_weights(|weight| {
+            // new edges start out with a weight of one, so fresh weights are drawn on that scale
+            let weight = match rng.random_bool(conf.weight_replace_prob) {
+                false => weight + rng.random_range(-conf.weight_mutate_power..=conf.weight_mutate_power),
+                true => rng.random_range(-1.0..=1.0),
             };
 
-            map.entry(&edge.head).or_insert(Accum::new()).push(eval);
-        }
+            weight.clamp(-conf.weight_max, conf.weight_max)
+        });
+
+        self.check(conf, Mutation::Weight);
+    }
+
+    // the nodes have to be evaluated in an order that the edges alone don't give, which is
+    // exactly what a network is compiled for
+    pub fn activate(&self, inputs: [f32; I]) -> [f32; O] {
+        Network::from(self).activate(inputs)
+    }
+
+    pub fn compat_dist(&self, other: &Self, conf: &Conf) -> f32 {
+        let matching = self.edges.innov_matching(&other.edges);
+        let disjoint = self.edges.innov_disjoint(&other.edges).len() + other.edges.innov_disjoint(&self.edges).len();
+        let excess = self.edges.innov_excess(&other.edges).len() + other.edges.innov_excess(&self.edges).len();
+
+        // the matching edges come in innovation order, so the weight differences are always
+        // summed in the same order, keeping runs reproducible down to the last bit
+        let weight = matching.iter().map(|(lhs, rhs)| (lhs.weight - rhs.weight).abs()).sum::<f32>();
 
-        array::from_fn::<_, O, _>(|idx| self.outputs.get(idx).unwrap().eval(&mut map))
+        let len = usize::max(self.edges.len(), other.edges.len()).max(1) as f32;
+        let weight = if !matching.is_empty() { weight / (matching.len() as f32) } else { 0.0 };
+
+        (conf.excess_coeff * (excess as f32) / len) + (conf.disjoint_coeff * (disjoint as f32) / len) + (conf.weight_coeff * weight)
     }
 
-    pub fn compat_dist(&self) -> f32 {
-        todo!()
+    /// Breeds a child with the structure of the fitter parent, or of `lhs` if neither is fitter.
+    ///
+    /// Every edge and node that both parents have is inherited from either of them at random,
+    /// while the disjoint and excess edges only ever come from the fitter parent.
+    pub fn crossover(lhs: &Self, rhs: &Self, rng: &mut impl Rng) -> Self {
+        let (fitter, other) = match rhs.fitness > lhs.fitness {
+            false => (lhs, rhs),
+            true => (rhs, lhs),
+        };
+
+        let edges = fitter.edges.innov_matching(&other.edges).into_iter()
+            .filter(|_| rng.random())
+            .map(|(_, edge)| (edge.innov, EdgeGene::from(edge)))
+            .collect::<HashMap<_, _>>();
+
+        let nodes = GenomeRepr::from(other).nodes.into_iter()
+            .map(|node| (node.innov, node))
+            .collect::<HashMap<_, _>>();
+
+        let mut repr = GenomeRepr::from(fitter);
+
+        for node in repr.nodes.iter_mut() {
+            if let Some(gene) = nodes.get(&node.innov).filter(|_| rng.random()) {
+                *node = gene.clone();
+            }
+        }
+
+        for edge in repr.edges.iter_mut() {
+            if let Some(gene) = edges.get(&edge.innov) {
+                *edge = gene.clone();
+            }
+        }
+
+        Genome::try_from(&repr).expect("both parents are valid genomes")
     }
+}
+
+// a derived clone would leave the edges of the copy pointing at the nodes of the original, so the
+// nodes are copied first and every edge is pointed at the copies of its own nodes
+impl<const I: usize, const O: usize> Clone for Genome<'_, I, O> {
+    fn clone(&self) -> Self {
+        let mut genome = Genome {
+            inputs: self.inputs.clone(),
+            outputs: self.outputs.clone(),
+            hiddens: Hiddens::default(),
+            edges: Edges::default(),
+            fitness: self.fitness,
+            age: self.age,
+            parent_species: self.parent_species,
+            lineage: self.lineage.clone(),
+        };
 
-    pub fn crossover(lhs: Self, rhs: Self, rng: &mut impl Rng) -> Self {
-        // choose edges for child that will be inherited from parents
-        // let int = Edges::innov_matching(&lhs, &rhs, rng);
-        // let diff = Edges::innov_disjoint(&lhs, &rhs, rng);
+        let mut tails = genome.inputs.iter()
+            .map(|input| (input.innov(), Tail::from(input.downgrade().upgrade())))
+            .collect::<HashMap<_, _>>();
 
-        let tails = HashSet::<Tail>::new();
-        let heads = HashSet::<Head>::new();
+        let mut heads = genome.outputs.iter()
+            .map(|output| (output.innov(), Head::from(output.downgrade().upgrade())))
+            .collect::<HashMap<_, _>>();
+
+        for hidden in self.hiddens.iter() {
+            let hidden = genome.hiddens.push(hidden.clone());
+            tails.insert(hidden.innov(), Tail::from(hidden));
+            heads.insert(hidden.innov(), Head::from(hidden));
+        }
+
+        for edge in self.edges.iter() {
+            genome.edges.insert(Edge {
+                tail: tails[&edge.tail.innov()].clone(),
+                head: heads[&edge.head.innov()].clone(),
+                enabled: edge.enabled.clone(),
+                ..*edge
+            });
+        }
 
-        todo!()
+        genome
     }
 }
 
@@ -88,6 +297,9 @@ impl<const I: usize, const O: usize> Default for Genome<'_, I, O> {
             hiddens: Hiddens::default(),
             edges: Edges::default(),
             fitness: Fitness::default(),
+            age: 0,
+            parent_species: None,
+            lineage: Lineage::default(),
         }
     }
 }
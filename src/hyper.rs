@@ -0,0 +1,132 @@
+extern crate alloc;
+use crate::{activ, genome::Genome, network::*};
+use core::{array, iter, ops::Range};
+use alloc::vec::Vec;
+
+/// A genome used as a compositional pattern-producing network, mapping a pair of `D` dimensional
+/// substrate coordinates to the weight of the connection between them.
+///
+/// The first `D` inputs receive the coordinates of the tail, the next `D` those of the head, and
+/// any remaining inputs are held at `1.0` so they can act as a bias.
+#[derive(Clone, Debug)]
+pub struct Cppn<const C: usize, const K: usize>(Network<C, K>);
+
+impl<const C: usize, const K: usize> Cppn<C, K> {
+    pub fn query<const D: usize>(&self, tail: [f32; D], head: [f32; D]) -> [f32; K] {
+        assert!(C >= 2 * D);
+
+        let mut inputs = [1.0; C];
+        inputs[..D].copy_from_slice(&tail);
+        inputs[D..2 * D].copy_from_slice(&head);
+
+        self.0.activate(inputs)
+    }
+}
+
+impl<const C: usize, const K: usize> From<&Genome<'_, C, K>> for Cppn<C, K> {
+    fn from(genome: &Genome<'_, C, K>) -> Self {
+        Self(Network::from(genome))
+    }
+}
+
+/// The geometry that a [`Cppn`] is painted onto: where every input, hidden and output node sits
+/// in `D` dimensional space.
+///
+/// Hidden nodes are grouped into layers. Every node of a layer is offered a connection from
+/// every node of the layer before it, with the inputs coming first and the outputs last.
+#[derive(Clone, Debug)]
+pub struct Substrate<const I: usize, const O: usize, const D: usize> {
+    pub inputs: [[f32; D]; I],
+    pub hiddens: Vec<Vec<[f32; D]>>,
+    pub outputs: [[f32; D]; O],
+    /// Weights whose magnitude doesn't exceed the threshold aren't expressed. Ignored when `leo`
+    /// is set.
+    pub threshold: f32,
+    pub max_weight: f32,
+    /// Uses the second output of the CPPN as a link expression output, only expressing a
+    /// connection when it is positive.
+    pub leo: bool,
+    pub activ: fn(f32) -> f32,
+    pub aggreg: fn(&[f32]) -> f32,
+}
+
+impl<const I: usize, const O: usize, const D: usize> Substrate<I, O, D> {
+    pub fn new(inputs: [[f32; D]; I], outputs: [[f32; D]; O]) -> Self {
+        Self {
+            inputs,
+            hiddens: Vec::new(),
+            outputs,
+            threshold: 0.2,
+            max_weight: 3.0,
+            leo: false,
+            activ: activ::identity,
+            aggreg: activ::sum,
+        }
+    }
+
+    /// Turns the raw outputs of a CPPN query into the weight of the connection, if it is
+    /// expressed at all.
+    pub fn express<const K: usize>(&self, outputs: [f32; K]) -> Option<f32> {
+        let weight = outputs[0];
+
+        match self.leo {
+            true => (outputs[1] > 0.0).then_some(weight * self.max_weight),
+            false => (weight.abs() > self.threshold).then(|| {
+                let scaled = (weight.abs() - self.threshold) / (1.0 - self.threshold);
+                weight.signum() * scaled * self.max_weight
+            }),
+        }
+    }
+
+    /// Queries `cppn` for the weight between every pair of nodes in adjacent layers and returns
+    /// the resulting network, leaving out every connection that isn't expressed.
+    pub fn build<const C: usize, const K: usize>(&self, cppn: &Genome<'_, C, K>) -> Network<I, O> {
+        assert!(K >= if self.leo { 2 } else { 1 });
+
+        let cppn = Cppn::from(cppn);
+        let mut neurons = Vec::new();
+        let mut conns = Vec::new();
+
+        let layers = iter::once(self.inputs.as_slice())
+            .chain(self.hiddens.iter().map(Vec::as_slice))
+            .chain(iter::once(self.outputs.as_slice()))
+            .collect::<Vec<_>>();
+
+        let mut start = 0;
+
+        for pair in layers.windows(2) {
+            let [tails, heads] = pair else { unreachable!() };
+
+            for head in heads.iter() {
+                let first = conns.len();
+
+                conns.extend(tails.iter().enumerate().filter_map(|(idx, tail)| {
+                    let weight = self.express(cppn.query(*tail, *head))?;
+                    Some(Conn { tail: start + idx, weight })
+                }));
+
+                neurons.push(self.neuron(first..conns.len()));
+            }
+
+            start += tails.len();
+        }
+
+        Network {
+            biases: [0.0; I],
+            neurons: neurons.into_boxed_slice(),
+            conns: conns.into_boxed_slice(),
+            outputs: array::from_fn::<_, O, _>(|idx| start + idx),
+        }
+    }
+
+    pub(crate) fn neuron(&self, conns: Range<usize>) -> Neuron {
+        Neuron {
+            bias: 0.0,
+            resp: 1.0,
+            tau: 1.0,
+            activ: self.activ,
+            aggreg: self.aggreg,
+            conns,
+        }
+    }
+}
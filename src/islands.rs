@@ -0,0 +1,156 @@
+extern crate alloc;
+use crate::{conf::Conf, fitness::Fitness, genome::Genome, lineage::Lineage, pop::*, repr::GenomeRepr};
+use core::mem;
+use alloc::{vec, vec::Vec};
+use hashbrown::HashMap;
+use rand::rngs::SmallRng;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Topology {
+    /// Every island sends its migrants to the next one, and the last to the first.
+    Ring,
+    /// Every island sends its migrants to every other island.
+    Full,
+}
+
+/// Several populations evolving side by side that exchange their best genomes every so often.
+#[derive(Debug)]
+pub struct Islands<'a, const I: usize, const O: usize> {
+    pub pops: Vec<Pop<'a, I, O>>,
+    /// The innovation numbers that every island numbers its offspring from, if they're shared.
+    shared: Option<Tracker>,
+    pub topology: Topology,
+    /// Generations between migrations.
+    pub interval: usize,
+    /// Number of the fittest genomes of an island that migrate, each replacing one of the least
+    /// fit genomes of the island it migrates to.
+    pub migrants: usize,
+    pub generation: usize,
+}
+
+impl<'a, const I: usize, const O: usize> Islands<'a, I, O> {
+    /// Creates `count` islands which either all share their innovation numbers, or each number
+    /// their own innovations.
+    pub fn new(conf: Conf, count: usize, shared: bool) -> Self {
+        let pops = (0..count)
+            .map(|idx| Pop::new(Conf { seed: conf.seed.wrapping_add(idx as u64), ..conf.clone() }))
+            .collect::<Vec<_>>();
+
+        // every island starts out with the same nodes and no edges, so any of them will do
+        let shared = pops.first().filter(|_| shared).map(|pop| pop.tracker.clone());
+
+        Self {
+            pops,
+            shared,
+            topology: Topology::Ring,
+            interval: 10,
+            migrants: 2,
+            generation: 0,
+        }
+    }
+
+    pub fn step(&mut self, eval: impl Fn(&Genome<'a, I, O>, &mut SmallRng) -> Fitness) {
+        for pop in self.pops.iter_mut() {
+            pop.evaluate(&eval);
+        }
+
+        self.generation += 1;
+
+        if self.interval > 0 && self.generation.is_multiple_of(self.interval) {
+            self.migrate();
+        }
+
+        for pop in self.pops.iter_mut() {
+            Self::within(&mut self.shared, pop, Pop::evolve);
+        }
+    }
+
+    pub fn champion(&self) -> Option<&Genome<'a, I, O>> {
+        self.pops.iter()
+            .filter_map(Pop::champion)
+            .max_by(|lhs, rhs| lhs.fitness.total_cmp(&rhs.fitness))
+    }
+
+    /// Sends the fittest genomes of every island to the islands that `topology` connects it to.
+    ///
+    /// Islands that number their own innovations give the same numbers to different genes, so a
+    /// migrant's hidden nodes and edges are numbered anew by the island it arrives on. Ids are
+    /// only unique within an island as well, so every migrant is given a fresh id whose parent is
+    /// the id it had on the island it left.
+    pub(crate) fn migrate(&mut self) {
+        let len = self.pops.len();
+
+        let migrants = self.pops.iter().map(|pop| {
+            let mut genomes = pop.genomes.iter().collect::<Vec<_>>();
+            genomes.sort_by(|lhs, rhs| rhs.fitness.total_cmp(&lhs.fitness));
+            genomes.into_iter().take(self.migrants).cloned().collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+
+        for (src, migrants) in migrants.into_iter().enumerate() {
+            let dsts = match self.topology {
+                Topology::Ring => vec![(src + 1) % len],
+                Topology::Full => (0..len).collect(),
+            };
+
+            for dst in dsts.into_iter().filter(|dst| *dst != src) {
+                let pop = &mut self.pops[dst];
+                let mut order = (0..pop.genomes.len()).collect::<Vec<_>>();
+                order.sort_by(|lhs, rhs| pop.genomes[*lhs].fitness.total_cmp(&pop.genomes[*rhs].fitness));
+
+                for (idx, migrant) in order.into_iter().zip(migrants.iter()) {
+                    let mut genome = match self.shared {
+                        Some(_) => migrant.clone(),
+                        None => Self::naturalize(pop, migrant),
+                    };
+
+                    genome.lineage = Lineage::new(pop.next_genome, vec![migrant.lineage.id], pop.generation);
+                    pop.next_genome += 1;
+                    pop.genomes[idx] = genome;
+                }
+            }
+        }
+    }
+
+    // rebuilds `migrant` with fresh innovation numbers for its hidden nodes and the edges numbered
+    // by `pop`, so that it only matches the genes of `pop` that run between the same inputs and
+    // outputs
+    fn naturalize(pop: &mut Pop<'a, I, O>, migrant: &Genome<'a, I, O>) -> Genome<'a, I, O> {
+        let mut repr = GenomeRepr::from(migrant);
+
+        pop.tracker.within(|| {
+            let nodes = repr.nodes[I + O..].iter_mut()
+                .map(|node| {
+                    let innov = Innov::next_node_innov();
+                    (mem::replace(&mut node.innov, innov), innov)
+                })
+                .collect::<HashMap<_, _>>();
+
+            for edge in repr.edges.iter_mut() {
+                edge.tail = nodes.get(&edge.tail).copied().unwrap_or(edge.tail);
+                edge.head = nodes.get(&edge.head).copied().unwrap_or(edge.head);
+                edge.innov = Innov::edge_innov(edge.tail, edge.head);
+            }
+
+            Genome {
+                fitness: migrant.fitness,
+                age: migrant.age,
+                ..Genome::try_from(&repr).expect("a migrant is a valid genome")
+            }
+        })
+    }
+
+    // runs `f` with the shared innovation numbers lent to `pop`, if the islands share them
+    fn within<T>(shared: &mut Option<Tracker>, pop: &mut Pop<'a, I, O>, f: impl FnOnce(&mut Pop<'a, I, O>) -> T) -> T {
+        if let Some(shared) = shared {
+            mem::swap(&mut pop.tracker, shared);
+        }
+
+        let result = f(pop);
+
+        if let Some(shared) = shared {
+            mem::swap(&mut pop.tracker, shared);
+        }
+
+        result
+    }
+}
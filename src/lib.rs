@@ -1,12 +1,35 @@
-#![cfg_attr(not(test), no_std)]
-#![feature(box_vec_non_null, cell_update, iter_collect_into, debug_closure_helpers, maybe_uninit_uninit_array, maybe_uninit_slice, thread_local)]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+#![feature(box_vec_non_null, core_float_math, debug_closure_helpers, thread_local)]
+#![cfg_attr(test, feature(test))]
 #![allow(dead_code, unused_variables)]
 // #![warn(clippy::cargo, clippy::style)]
 
+mod activ;
 mod arena;
+mod checkpoint;
+mod codec;
+mod coevo;
+mod conf;
+mod ctrnn;
+mod diff;
+mod dot;
 mod edge;
+mod elites;
+mod es;
 mod fitness;
 mod genome;
+mod hyper;
+mod islands;
+mod lineage;
+mod network;
 mod node;
+mod novelty;
 mod pop;
+mod report;
+mod repr;
+mod speciation;
+mod species;
+mod svg;
+mod validate;
+#[cfg(test)]
 mod tests;
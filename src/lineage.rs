@@ -0,0 +1,142 @@
+extern crate alloc;
+use crate::{pop::Pop, report::Reporter};
+use core::{cmp::Reverse, fmt};
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use hashbrown::HashSet;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Mutation {
+    AddEdge,
+    SplitEdge,
+    Weight,
+    Tau,
+    Activation,
+}
+
+impl fmt::Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AddEdge => write!(f, "add_edge"),
+            Self::SplitEdge => write!(f, "split_edge"),
+            Self::Weight => write!(f, "weight"),
+            Self::Tau => write!(f, "tau"),
+            Self::Activation => write!(f, "activation"),
+        }
+    }
+}
+
+/// Where a genome came from.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Lineage {
+    /// Id of the genome, unique within its population.
+    pub id: usize,
+    /// Ids of the genomes it was bred from. Empty for the genomes a population starts out with,
+    /// and a single id when a genome was bred from itself.
+    pub parents: Vec<usize>,
+    /// Mutations applied to it after it was bred, in the order they were applied.
+    pub mutations: Vec<Mutation>,
+    /// Generation it was born in.
+    pub born: usize,
+}
+
+impl Lineage {
+    pub fn new(id: usize, parents: Vec<usize>, born: usize) -> Self {
+        Self { id, parents, mutations: Vec::new(), born }
+    }
+}
+
+/// A [`Reporter`] that keeps the lineage of every genome that was ever evaluated, so that the
+/// ancestry of any of them can be traced back to the first generation.
+#[derive(Clone, Debug, Default)]
+pub struct Phylogeny {
+    pub genomes: BTreeMap<usize, Lineage>,
+}
+
+impl Phylogeny {
+    /// Returns the lineage of the genome with the given id and of every one of its ancestors,
+    /// youngest first.
+    pub fn ancestry(&self, id: usize) -> Vec<&Lineage> {
+        let mut stack = vec![id];
+        let mut seen = HashSet::new();
+        let mut ancestry = Vec::new();
+
+        while let Some(id) = stack.pop() {
+            if let Some(lineage) = self.genomes.get(&id).filter(|_| seen.insert(id)) {
+                stack.extend(lineage.parents.iter().copied());
+                ancestry.push(lineage);
+            }
+        }
+
+        // ids are handed out in the order that genomes are born
+        ancestry.sort_by_key(|lineage| Reverse(lineage.id));
+        ancestry
+    }
+
+    /// Formats every recorded lineage as CSV. Parents and mutations are joined by spaces.
+    pub fn csv(&self) -> impl fmt::Display + '_ {
+        fmt::from_fn(move |f| {
+            writeln!(f, "id,born,parents,mutations")?;
+
+            for lineage in self.genomes.values() {
+                write!(f, "{},{},", lineage.id, lineage.born)?;
+                join(f, &lineage.parents)?;
+                write!(f, ",")?;
+                join(f, &lineage.mutations)?;
+                writeln!(f)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Formats the ancestry of the genome with the given id as a Graphviz digraph, with an edge
+    /// from every parent to its child. Every genome is labelled with the generation it was born
+    /// in and the mutations it went through.
+    pub fn dot(&self, id: usize) -> impl fmt::Display + '_ {
+        fmt::from_fn(move |f| {
+            let ancestry = self.ancestry(id);
+
+            writeln!(f, "digraph phylogeny {{")?;
+
+            for lineage in ancestry.iter() {
+                write!(f, "    {} [label=\"{}\\ngeneration {}\\n", lineage.id, lineage.id, lineage.born)?;
+                join(f, &lineage.mutations)?;
+                writeln!(f, "\"];")?;
+
+                for parent in lineage.parents.iter() {
+                    writeln!(f, "    {parent} -> {};", lineage.id)?;
+                }
+            }
+
+            writeln!(f, "}}")
+        })
+    }
+
+    fn record<const I: usize, const O: usize>(&mut self, pop: &Pop<'_, I, O>) {
+        for genome in pop.genomes.iter() {
+            self.genomes.entry(genome.lineage.id).or_insert_with(|| genome.lineage.clone());
+        }
+    }
+}
+
+// the offspring left behind by the last generation are recorded too, so that the champion can
+// be traced once they've been evaluated
+impl<const I: usize, const O: usize> Reporter<I, O> for Phylogeny {
+    fn generation_end(&mut self, pop: &Pop<'_, I, O>) { self.record(pop); }
+    fn run_complete(&mut self, pop: &Pop<'_, I, O>) { self.record(pop); }
+}
+
+fn join(f: &mut fmt::Formatter<'_>, values: &[impl fmt::Display]) -> fmt::Result {
+    for (idx, value) in values.iter().enumerate() {
+        match idx {
+            0 => write!(f, "{value}")?,
+            _ => write!(f, " {value}")?,
+        }
+    }
+
+    Ok(())
+}
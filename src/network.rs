@@ -0,0 +1,256 @@
+extern crate alloc;
+use crate::{activ, genome::Genome, node::*};
+use core::{array, cmp::Reverse, ops::Range, ptr};
+use alloc::{boxed::Box, collections::BinaryHeap, vec, vec::Vec};
+use hashbrown::HashMap;
+
+/// Number of samples that [`Network::activate_batch`] evaluates side by side.
+const LANES: usize = 8;
+
+#[derive(Clone, Debug)]
+pub struct Neuron {
+    pub bias: f32,
+    pub resp: f32,
+    pub tau: f32,
+    pub activ: fn(f32) -> f32,
+    pub aggreg: fn(&[f32]) -> f32,
+    pub conns: Range<usize>,
+}
+
+impl Neuron {
+    pub fn eval(&self, weighted: &[f32]) -> f32 {
+        // an unconnected node behaves as if it received no signal at all
+        let input = match weighted.is_empty() {
+            false => (self.aggreg)(weighted),
+            true => 0.0,
+        };
+
+        (self.activ)(self.bias + (self.resp * input))
+    }
+
+    // sums and means are taken over every lane at once, which covers the aggregations that nodes
+    // are given by default without gathering the inputs of each sample into `lane` one by one
+    fn eval_lanes(&self, conns: &[Conn], values: &[[f32; LANES]], lane: &mut Vec<f32>) -> [f32; LANES] {
+        let sum = ptr::fn_addr_eq(self.aggreg, activ::sum as fn(&[f32]) -> f32);
+        let mean = ptr::fn_addr_eq(self.aggreg, activ::mean as fn(&[f32]) -> f32);
+
+        let input = match (conns.is_empty(), sum || mean) {
+            (true, _) => [0.0; LANES],
+            (false, true) => {
+                let mut input = [0.0; LANES];
+
+                for conn in conns.iter() {
+                    for (input, value) in input.iter_mut().zip(&values[conn.tail]) {
+                        *input += conn.weight * value;
+                    }
+                }
+
+                match mean {
+                    false => input,
+                    true => input.map(|input| input / conns.len() as f32),
+                }
+            }
+            (false, false) => array::from_fn::<_, LANES, _>(|row| {
+                lane.clear();
+                lane.extend(conns.iter().map(|conn| conn.weight * values[conn.tail][row]));
+                (self.aggreg)(lane)
+            }),
+        };
+
+        input.map(|input| (self.activ)(self.bias + (self.resp * input)))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Conn {
+    pub tail: usize,
+    pub weight: f32,
+}
+
+/// A genome flattened into contiguous buffers so it can be evaluated without chasing pointers.
+///
+/// Every node lives in a slot: inputs occupy `0..I` and the remaining nodes follow in activation
+/// order, so a node only ever reads slots that come before its own.
+#[derive(Clone, Debug)]
+pub struct Network<const I: usize, const O: usize> {
+    pub(crate) biases: [f32; I],
+    pub(crate) neurons: Box<[Neuron]>,
+    pub(crate) conns: Box<[Conn]>,
+    pub(crate) outputs: [usize; O],
+}
+
+impl<const I: usize, const O: usize> Network<I, O> {
+    pub fn activate(&self, inputs: [f32; I]) -> [f32; O] {
+        let mut values = vec![0.0; I + self.neurons.len()];
+        self.step(inputs, &mut values)
+    }
+
+    // a connection whose tail comes at or after its head in slot order reads whatever value the
+    // tail was left with by the previous call
+    fn step(&self, inputs: [f32; I], values: &mut [f32]) -> [f32; O] {
+        for (value, (input, bias)) in values.iter_mut().zip(inputs.iter().zip(&self.biases)) {
+            *value = bias + input;
+        }
+
+        let mut weighted = Vec::new();
+
+        for (idx, neuron) in self.neurons.iter().enumerate() {
+            weighted.clear();
+            weighted.extend(self.conns[neuron.conns.clone()].iter().map(|conn| conn.weight * values[conn.tail]));
+            values[I + idx] = neuron.eval(&weighted);
+        }
+
+        array::from_fn::<_, O, _>(|idx| values[self.outputs[idx]])
+    }
+
+    /// Evaluates every row of `inputs`, writing the result for each row into the same row of
+    /// `outputs`.
+    ///
+    /// Rows are processed [`LANES`] at a time with each slot holding one value per row. Nodes
+    /// that sum or average their inputs are weighted and aggregated with straight-line loops over
+    /// those fixed size arrays that the compiler is free to vectorize, while any other
+    /// aggregation is called once per row. The activation function is called once per row.
+    pub fn activate_batch(&self, inputs: &[[f32; I]], outputs: &mut [[f32; O]]) {
+        assert_eq!(inputs.len(), outputs.len());
+
+        let mut values = vec![[0.0; LANES]; I + self.neurons.len()];
+        let mut lane = Vec::new();
+
+        for (inputs, outputs) in inputs.chunks(LANES).zip(outputs.chunks_mut(LANES)) {
+            for (idx, (bias, slot)) in self.biases.iter().zip(values.iter_mut()).enumerate() {
+                for (value, row) in slot.iter_mut().zip(inputs) {
+                    *value = bias + row[idx];
+                }
+            }
+
+            for (idx, neuron) in self.neurons.iter().enumerate() {
+                values[I + idx] = neuron.eval_lanes(&self.conns[neuron.conns.clone()], &values, &mut lane);
+            }
+
+            for (row, output) in outputs.iter_mut().enumerate() {
+                *output = array::from_fn::<_, O, _>(|idx| values[self.outputs[idx]][row]);
+            }
+        }
+    }
+}
+
+impl<const I: usize, const O: usize> From<&Genome<'_, I, O>> for Network<I, O> {
+    fn from(genome: &Genome<'_, I, O>) -> Self {
+        let mut nodes = genome.hiddens.iter().map(Head::from)
+            .chain(genome.outputs.iter().map(Head::from))
+            .collect::<Vec<_>>();
+
+        // layers are only a hint, since nothing keeps them settled in a recurrent genome, so they
+        // just break ties between the nodes that are ready at the same time
+        nodes.sort_by_key(|head| (head.layer(), head.innov()));
+
+        let position = nodes.iter().enumerate()
+            .map(|(idx, head)| (head.innov(), idx))
+            .collect::<HashMap<_, _>>();
+
+        let mut pending = vec![0; nodes.len()];
+        let mut outgoing = vec![Vec::new(); nodes.len()];
+
+        // recurrent edges read whatever their tail was left with by the previous step, so only the
+        // forward edges out of hidden nodes hold a node back
+        for edge in genome.edges.iter().filter(|edge| !edge.recurrent) {
+            if let Some(tail) = edge.tail.hidden() {
+                let (tail, head) = (position[&tail.innov()], position[&edge.head.innov()]);
+                pending[head] += 1;
+                outgoing[tail].push(head);
+            }
+        }
+
+        let mut ready = (0..nodes.len())
+            .filter(|idx| pending[*idx] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+
+        let mut heads = Vec::with_capacity(nodes.len());
+        let mut placed = vec![false; nodes.len()];
+
+        while let Some(Reverse(idx)) = ready.pop() {
+            heads.push(nodes[idx].clone());
+            placed[idx] = true;
+
+            for head in outgoing[idx].iter().copied() {
+                pending[head] -= 1;
+
+                if pending[head] == 0 {
+                    ready.push(Reverse(head));
+                }
+            }
+        }
+
+        // only a genome whose forward edges close a cycle leaves anything behind
+        heads.extend(nodes.iter().zip(placed).filter(|(_, placed)| !placed).map(|(head, _)| head.clone()));
+
+        let slots = heads.iter().enumerate()
+            .map(|(idx, head)| (head.clone(), I + idx))
+            .collect::<HashMap<_, _>>();
+
+        let mut incoming = HashMap::<&Head, Vec<Conn>>::new();
+
+        for edge in genome.edges.iter().filter(|edge| edge.enabled.get()) {
+            let tail = match edge.tail {
+                Tail::Input(input) => input.index(),
+                Tail::Hidden(hidden) => slots[&Head::from(hidden)],
+            };
+
+            incoming.entry(&edge.head).or_default().push(Conn { tail, weight: edge.weight });
+        }
+
+        let mut neurons = Vec::with_capacity(heads.len());
+        let mut conns = Vec::new();
+
+        for head in heads.iter() {
+            let start = conns.len();
+            conns.extend(incoming.remove(head).into_iter().flatten());
+
+            neurons.push(Neuron {
+                bias: head.bias(),
+                resp: head.response(),
+                tau: head.tau(),
+                activ: head.activation(),
+                aggreg: head.aggregator(),
+                conns: start..conns.len(),
+            });
+        }
+
+        Self {
+            biases: array::from_fn::<_, I, _>(|idx| genome.inputs[idx].bias()),
+            neurons: neurons.into_boxed_slice(),
+            conns: conns.into_boxed_slice(),
+            outputs: array::from_fn::<_, O, _>(|idx| slots[&Head::from(&genome.outputs[idx])]),
+        }
+    }
+}
+
+/// A network whose nodes keep their values between calls to [`RecurrentNetwork::activate`].
+///
+/// Every node is still evaluated after the tails of its forward edges, so an edge that feeds
+/// forward sees the value its tail produced during the same step while a recurrent edge sees the
+/// value from the previous step.
+#[derive(Clone, Debug)]
+pub struct RecurrentNetwork<const I: usize, const O: usize> {
+    network: Network<I, O>,
+    values: Box<[f32]>,
+}
+
+impl<const I: usize, const O: usize> RecurrentNetwork<I, O> {
+    pub fn activate(&mut self, inputs: [f32; I]) -> [f32; O] {
+        self.network.step(inputs, &mut self.values)
+    }
+
+    pub fn reset(&mut self) {
+        self.values.fill(0.0);
+    }
+}
+
+impl<const I: usize, const O: usize> From<&Genome<'_, I, O>> for RecurrentNetwork<I, O> {
+    fn from(genome: &Genome<'_, I, O>) -> Self {
+        let network = Network::from(genome);
+        let values = vec![0.0; I + network.neurons.len()].into_boxed_slice();
+        Self { network, values }
+    }
+}
@@ -67,6 +67,20 @@ impl Node for Head<'_> {
         }
     }
 
+    fn activation(&self) -> fn(f32) -> f32 {
+        match self {
+            Self::Hidden(hidden) => hidden.activation(),
+            Self::Output(output) => output.activation(),
+        }
+    }
+
+    fn update_activation(&self, activation: fn(f32) -> f32) {
+        match self {
+            Self::Hidden(hidden) => hidden.update_activation(activation),
+            Self::Output(output) => output.update_activation(activation),
+        }
+    }
+
     fn response(&self) -> f32 {
         match self {
             Self::Hidden(hidden) => hidden.response(),
@@ -74,6 +88,20 @@ impl Node for Head<'_> {
         }
     }
 
+    fn tau(&self) -> f32 {
+        match self {
+            Self::Hidden(hidden) => hidden.tau(),
+            Self::Output(output) => output.tau(),
+        }
+    }
+
+    fn update_tau(&self, tau: f32) {
+        match self {
+            Self::Hidden(hidden) => hidden.update_tau(tau),
+            Self::Output(output) => output.update_tau(tau),
+        }
+    }
+
     fn aggregator(&self) -> fn(&[f32]) -> f32 {
         match self {
             Self::Hidden(hidden) => hidden.aggregator(),
@@ -1,6 +1,6 @@
 extern crate alloc;
-use crate::{arena::Arena, edge::Edge, node::{Accum, *}, pop::Pop};
-use core::{cell::Cell, cmp, fmt, hash::{Hash, Hasher}, ptr, slice};
+use crate::{activ, arena::Arena, edge::Edge, node::{Accum, *}, pop::Innov, repr::NodeGene};
+use core::{cell::Cell, cmp, fmt, hash::{Hash, Hasher}, ptr};
 use hashbrown::HashMap;
 
 #[derive(Clone)]
@@ -9,6 +9,7 @@ pub struct Hidden {
     layer: Cell<usize>,
     bias: f32,
     resp: f32,
+    tau: Cell<f32>,
     activ: Cell<fn(f32) -> f32>,
     aggreg: fn(&[f32]) -> f32,
 }
@@ -19,16 +20,27 @@ impl Hidden {
     }
 
     pub fn from_edge(edge: &Edge) -> Self {
-        let curr_level = edge.tail.layer();
-        edge.head.update_layer(curr_level + 1);
-
         Self {
-            layer: Cell::new(curr_level),
-            activ: Cell::new(|x| x),
-            aggreg: |values| values.iter().sum::<f32>() / (values.len() as f32),
+            layer: Cell::new(edge.tail.layer() + 1),
+            activ: Cell::new(activ::identity),
+            aggreg: activ::mean,
             resp: 1.0,
+            tau: Cell::new(1.0),
             bias: 0.0,
-            innov: Pop::next_node_innov(),
+            innov: Innov::next_node_innov(),
+        }
+    }
+
+    // the layer is left for whoever restores the edges to work out
+    pub(crate) fn from_gene(gene: &NodeGene, activ: fn(f32) -> f32, aggreg: fn(&[f32]) -> f32) -> Self {
+        Self {
+            innov: gene.innov,
+            layer: Cell::new(1),
+            bias: gene.bias,
+            resp: gene.response,
+            tau: Cell::new(gene.tau),
+            activ: Cell::new(activ),
+            aggreg,
         }
     }
 
@@ -60,7 +72,11 @@ impl Node for Hidden {
     fn innov(&self) -> usize { self.innov }
     fn update_layer(&self, layer: usize) { self.layer.update(|current| cmp::max(current, layer)); }
     fn activate(&self, x: f32) -> f32 { self.activ.get()(x) }
+    fn activation(&self) -> fn(f32) -> f32 { self.activ.get() }
+    fn update_activation(&self, activation: fn(f32) -> f32) { self.activ.set(activation); }
     fn response(&self) -> f32 { self.resp }
+    fn tau(&self) -> f32 { self.tau.get() }
+    fn update_tau(&self, tau: f32) { self.tau.set(tau); }
     fn aggregator(&self) -> fn(&[f32]) -> f32 { self.aggreg }
 }
 
@@ -86,20 +102,35 @@ pub struct Hiddens {
 
 impl Hiddens {
     fn insert<'a>(&mut self, edge: &Edge) -> &'a Hidden {
+        self.push(Hidden::from_edge(edge))
+    }
+
+    pub(crate) fn push<'a>(&mut self, hidden: Hidden) -> &'a Hidden {
         self.len += 1;
-        self.arena.push(Hidden::from_edge(edge))
+        self.arena.push(hidden)
     }
 
     pub fn split_edge<'a>(&mut self, edge: &Edge<'a>) -> (Edge<'a>, Edge<'a>) {
         edge.enabled.set(false);
         let middle = self.insert(edge);
         let first = Edge::new(edge.tail.clone(), middle);
-        let last = Edge::new(middle, edge.head.clone());
+
+        // the middle node takes the place of the tail, so the edge into the head still feeds back
+        let last = match edge.recurrent {
+            false => Edge::new(middle, edge.head.clone()),
+            true => Edge::recurrent(middle, edge.head.clone()),
+        };
+
         (first, last)
     }
 
-    pub fn iter(&self) -> slice::Iter<'_, Hidden> {
-        todo!()
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Iterates over the hidden nodes in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &Hidden> + Clone {
+        self.arena.iter()
     }
 }
 
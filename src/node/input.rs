@@ -1,5 +1,5 @@
 extern crate alloc;
-use crate::{node::Node, pop::Pop};
+use crate::{node::Node, pop::Innov, repr::NodeGene};
 use core::{fmt, hash, ptr};
 
 #[derive(Clone, Debug, PartialEq)]
@@ -14,10 +14,14 @@ impl Input {
     }
 
     pub fn new(innov: usize) -> Self {
-        Pop::next_node_innov();
+        Innov::reserve_node_innov(innov);
         Self { innov, bias: 0.0 }
     }
 
+    pub(crate) fn from_gene(gene: &NodeGene) -> Self {
+        Self { innov: gene.innov, bias: gene.bias }
+    }
+
     // we can use self.innov as the idx for any input node
     pub fn index(&self) -> usize {
         self.innov
@@ -34,7 +38,11 @@ impl Node for Input {
     fn innov(&self) -> usize { self.innov }
     fn update_layer(&self, layer: usize) { panic!(); }
     fn activate(&self, x: f32) -> f32 { panic!(); }
+    fn activation(&self) -> fn(f32) -> f32 { panic!(); }
+    fn update_activation(&self, activation: fn(f32) -> f32) { panic!(); }
     fn response(&self) -> f32 { panic!(); }
+    fn tau(&self) -> f32 { panic!(); }
+    fn update_tau(&self, tau: f32) { panic!(); }
     fn aggregator(&self) -> fn(&[f32]) -> f32 { panic!(); }
 }
 
@@ -18,7 +18,11 @@ pub trait Node {
     fn innov(&self) -> usize;
     fn update_layer(&self, layer: usize);
     fn activate(&self, x: f32) -> f32;
+    fn activation(&self) -> fn(f32) -> f32;
+    fn update_activation(&self, activation: fn(f32) -> f32);
     fn response(&self) -> f32;
+    fn tau(&self) -> f32;
+    fn update_tau(&self, tau: f32);
     fn aggregator(&self) -> fn(&[f32]) -> f32;
 }
 
@@ -1,14 +1,15 @@
 extern crate alloc;
-use crate::{pop::Pop, node::*, node::Accum};
+use crate::{activ, pop::Innov, node::*, node::Accum, repr::NodeGene};
 use core::{cell::Cell, cmp, fmt, hash::{Hash, Hasher}, ptr};
 use hashbrown::HashMap;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct Output {
     layer: Cell<usize>,
     activation: Cell<fn(f32) -> f32>,
     aggregator: fn(&[f32]) -> f32,
     response: f32,
+    tau: Cell<f32>,
     bias: f32,
     innov: usize,
 }
@@ -19,14 +20,27 @@ impl Output {
     }
 
     pub fn new<const I: usize>(innov: usize) -> Self {
-        Pop::next_node_innov();
+        Innov::reserve_node_innov(I + innov);
         Self {
             layer: 1.into(),
-            activation: Cell::new(|x| x),
-            aggregator: |values| values.iter().sum::<f32>() / (values.len() as f32),
+            activation: Cell::new(activ::identity),
+            aggregator: activ::mean,
             response: 1.0,
+            tau: Cell::new(1.0),
             bias: 0.0,
-            innov: I - innov,
+            innov: I + innov,
+        }
+    }
+
+    pub(crate) fn from_gene(gene: &NodeGene, activation: fn(f32) -> f32, aggregator: fn(&[f32]) -> f32) -> Self {
+        Self {
+            layer: 1.into(),
+            activation: Cell::new(activation),
+            aggregator,
+            response: gene.response,
+            tau: Cell::new(gene.tau),
+            bias: gene.bias,
+            innov: gene.innov,
         }
     }
 
@@ -46,10 +60,22 @@ impl Node for Output {
     fn innov(&self) -> usize { self.innov }
     fn update_layer(&self, layer: usize) { self.layer.update(|current| cmp::max(current, layer)); }
     fn activate(&self, x: f32) -> f32 { self.activation.get()(x)}
+    fn activation(&self) -> fn(f32) -> f32 { self.activation.get() }
+    fn update_activation(&self, activation: fn(f32) -> f32) { self.activation.set(activation); }
     fn response(&self) -> f32 { self.response }
+    fn tau(&self) -> f32 { self.tau.get() }
+    fn update_tau(&self, tau: f32) { self.tau.set(tau); }
     fn aggregator(&self) -> fn(&[f32]) -> f32 { self.aggregator }
 }
 
+// the functions are left out since their addresses aren't guaranteed to be unique, which leaves
+// the same fields that are hashed
+impl PartialEq for Output {
+    fn eq(&self, other: &Self) -> bool {
+        self.layer == other.layer && self.response == other.response && self.bias == other.bias && self.innov == other.innov
+    }
+}
+
 impl Eq for Output {}
 
 impl Hash for Output {
@@ -53,13 +53,61 @@ impl Node for Tail<'_> {
         }
     }
 
-    fn update_layer(&self, layer: usize) { todo!(); }
+    fn update_layer(&self, layer: usize) {
+        match self {
+            Self::Input(input) => input.update_layer(layer),
+            Self::Hidden(hidden) => hidden.update_layer(layer),
+        }
+    }
+
+    fn activate(&self, x: f32) -> f32 {
+        match self {
+            Self::Input(input) => input.activate(x),
+            Self::Hidden(hidden) => hidden.activate(x),
+        }
+    }
+
+    fn activation(&self) -> fn(f32) -> f32 {
+        match self {
+            Self::Input(input) => input.activation(),
+            Self::Hidden(hidden) => hidden.activation(),
+        }
+    }
+
+    fn update_activation(&self, activation: fn(f32) -> f32) {
+        match self {
+            Self::Input(input) => input.update_activation(activation),
+            Self::Hidden(hidden) => hidden.update_activation(activation),
+        }
+    }
 
-    fn activate(&self, x: f32) -> f32 { todo!(); }
+    fn response(&self) -> f32 {
+        match self {
+            Self::Input(input) => input.response(),
+            Self::Hidden(hidden) => hidden.response(),
+        }
+    }
+
+    fn tau(&self) -> f32 {
+        match self {
+            Self::Input(input) => input.tau(),
+            Self::Hidden(hidden) => hidden.tau(),
+        }
+    }
 
-    fn response(&self) -> f32 { todo!(); }
+    fn update_tau(&self, tau: f32) {
+        match self {
+            Self::Input(input) => input.update_tau(tau),
+            Self::Hidden(hidden) => hidden.update_tau(tau),
+        }
+    }
 
-    fn aggregator(&self) -> fn(&[f32]) -> f32 { todo!(); }
+    fn aggregator(&self) -> fn(&[f32]) -> f32 {
+        match self {
+            Self::Input(input) => input.aggregator(),
+            Self::Hidden(hidden) => hidden.aggregator(),
+        }
+    }
 }
 
 impl fmt::Pointer for Tail<'_> {
@@ -0,0 +1,111 @@
+extern crate alloc;
+use crate::{fitness::Fitness, genome::Genome};
+use core::f32::math;
+use alloc::vec::Vec;
+
+/// Describes what a genome did while it was evaluated, such as where a maze runner ended up.
+pub type Behavior = Vec<f32>;
+
+/// Novelty search, which rewards genomes for behaving differently from the rest of the population
+/// and from every sufficiently novel behavior seen in the past.
+///
+/// The novelty of a behavior is its mean distance to its `k` nearest neighbours among the other
+/// behaviors of the current population and the archive. Behaviors more novel than `threshold`
+/// are admitted into the archive, and the threshold is raised when too many are admitted at once
+/// and lowered when none have been admitted for a while.
+#[derive(Clone, Debug)]
+pub struct Novelty {
+    archive: Vec<Behavior>,
+    stale: usize,
+    pub k: usize,
+    pub threshold: f32,
+    /// Share of a genome's fitness that comes from the objective rather than from novelty, so
+    /// `0.0` is pure novelty search.
+    pub objective: f32,
+    /// Admissions in a single generation above which the threshold is raised.
+    pub raise_after: usize,
+    /// Generations without an admission after which the threshold is lowered.
+    pub lower_after: usize,
+}
+
+impl Novelty {
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            archive: Vec::new(),
+            stale: 0,
+            k: 15,
+            threshold,
+            objective: 0.0,
+            raise_after: 4,
+            lower_after: 5,
+        }
+    }
+
+    pub fn archive(&self) -> &[Behavior] {
+        &self.archive
+    }
+
+    /// Returns the novelty of every behavior in `behaviors`, which should hold one behavior for
+    /// every genome of the current population, then updates the archive.
+    pub fn score(&mut self, behaviors: &[Behavior]) -> Vec<f32> {
+        let scores = behaviors.iter().enumerate().map(|(idx, behavior)| {
+            let mut dists = behaviors.iter().enumerate()
+                .filter(|(other, _)| *other != idx)
+                .map(|(_, other)| dist(behavior, other))
+                .chain(self.archive.iter().map(|other| dist(behavior, other)))
+                .collect::<Vec<_>>();
+
+            dists.sort_unstable_by(f32::total_cmp);
+
+            match self.k.min(dists.len()) {
+                0 => 0.0,
+                k => dists[..k].iter().sum::<f32>() / (k as f32),
+            }
+        }).collect::<Vec<_>>();
+
+        let len = self.archive.len();
+
+        self.archive.extend(behaviors.iter().zip(&scores)
+            .filter(|(_, score)| **score > self.threshold)
+            .map(|(behavior, _)| behavior.clone()));
+
+        match self.archive.len() - len {
+            0 => {
+                self.stale += 1;
+
+                if self.stale >= self.lower_after {
+                    self.threshold *= 0.95;
+                    self.stale = 0;
+                }
+            }
+            admitted => {
+                self.stale = 0;
+
+                if admitted > self.raise_after {
+                    self.threshold *= 1.2;
+                }
+            }
+        }
+
+        scores
+    }
+
+    /// Evaluates every genome and sets its fitness to a blend of its objective fitness and its
+    /// novelty, weighted by `objective`.
+    pub fn evaluate<const I: usize, const O: usize>(
+        &mut self,
+        genomes: &mut [Genome<'_, I, O>],
+        eval: impl Fn(&Genome<'_, I, O>) -> (Fitness, Behavior),
+    ) {
+        let (objectives, behaviors) = genomes.iter().map(eval).unzip::<_, _, Vec<_>, Vec<_>>();
+        let scores = self.score(&behaviors);
+
+        for ((genome, objective), novelty) in genomes.iter_mut().zip(objectives).zip(scores) {
+            genome.fitness = Fitness::from((self.objective * *objective) + ((1.0 - self.objective) * novelty));
+        }
+    }
+}
+
+fn dist(lhs: &[f32], rhs: &[f32]) -> f32 {
+    math::sqrt(lhs.iter().zip(rhs).map(|(lhs, rhs)| (lhs - rhs) * (lhs - rhs)).sum())
+}
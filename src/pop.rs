@@ -2,23 +2,72 @@
 //! on MacOS.
 
 extern crate alloc;
-use crate::node::{Tail, Head};
-use core::cell::*;
+use crate::{conf::Conf, fitness::*, genome::Genome, lineage::Lineage, node::{Tail, Head}, report::Reporter, species::Species};
+use core::{cell::*, cmp, f32::math, mem, ptr};
+use alloc::{vec, vec::Vec};
 use hashbrown::HashMap;
+use rand::{Rng, SeedableRng, rngs::SmallRng, seq::IndexedRandom};
+#[cfg(feature = "parallel")]
+use crate::network::Network;
 
 #[thread_local]
 static EDGES: LazyCell<RefCell<HashMap<(usize, usize), usize>>> = LazyCell::new(Default::default);
 #[thread_local]
 static NODES: Cell<usize> = Cell::new(0);
 
-pub struct Pop;
+pub struct Innov;
+
+/// A set of innovation numbers held outside of the thread that hands them out, so that several
+/// populations on the same thread can each number their innovations independently.
+#[derive(Clone, Debug, Default)]
+pub struct Tracker {
+    pub(crate) edges: HashMap<(usize, usize), usize>,
+    pub(crate) nodes: usize,
+}
+
+impl Tracker {
+    /// Runs `f` with these innovation numbers swapped into the current thread, so that whatever
+    /// `f` numbers is numbered from them.
+    pub fn within<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        Innov::swap(self);
+        let result = f();
+        Innov::swap(self);
+        result
+    }
+}
+
+impl Innov {
+    /// Exchanges the innovation numbers of the current thread with those held by `tracker`.
+    pub fn swap(tracker: &mut Tracker) {
+        mem::swap(&mut *EDGES.borrow_mut(), &mut tracker.edges);
+        tracker.nodes = NODES.replace(tracker.nodes);
+    }
+
+    /// Returns a copy of the innovation numbers of the current thread.
+    pub fn snapshot() -> Tracker {
+        Tracker { edges: EDGES.borrow().clone(), nodes: NODES.get() }
+    }
 
-impl Pop {
     pub fn next_edge_innov(tail: &Tail, head: &Head) -> usize {
-        let key = (tail.innov(), head.innov());
+        Self::edge_innov(tail.innov(), head.innov())
+    }
+
+    // the edge innovation number of the nodes with these innovation numbers, handed out the
+    // first time they're connected
+    pub(crate) fn edge_innov(tail: usize, head: usize) -> usize {
         let mut edges = EDGES.borrow_mut();
-        let next = edges.len();
-        *edges.entry(key).or_insert(next)
+        // edges registered from a loaded genome can leave gaps, so counting them isn't enough
+        let next = edges.values().max().map_or(0, |max| max + 1);
+        *edges.entry((tail, head)).or_insert(next)
+    }
+
+    /// Keeps the current thread from handing `innov` out to any other edge than the one between
+    /// the nodes with these innovation numbers.
+    pub(crate) fn reserve_edge_innov(tail: usize, head: usize, innov: usize) {
+        let mut edges = EDGES.borrow_mut();
+        // a pair that's already numbered keeps the larger number, so the next edge stays past both
+        let entry = edges.entry((tail, head)).or_insert(innov);
+        *entry = cmp::max(*entry, innov);
     }
 
     pub fn next_node_innov() -> usize {
@@ -26,5 +75,355 @@ impl Pop {
         NODES.update(|nodes| nodes + 1);
         next
     }
+
+    /// Keeps the current thread from handing out `innov`, or any node innovation number before
+    /// it, from now on.
+    pub fn reserve_node_innov(innov: usize) {
+        NODES.update(|nodes| cmp::max(nodes, innov + 1));
+    }
 }
 
+#[derive(Debug)]
+pub struct Pop<'a, const I: usize, const O: usize> {
+    pub genomes: Vec<Genome<'a, I, O>>,
+    pub species: Vec<Species<'a, I, O>>,
+    pub conf: Conf,
+    pub generation: usize,
+    pub(crate) seed: u64,
+    pub(crate) streams: u64,
+    pub(crate) next_species: usize,
+    pub(crate) next_genome: usize,
+    /// The innovation numbers of this population alone, so that a run only ever depends on its
+    /// seed and not on whatever else was numbered on the same thread.
+    pub(crate) tracker: Tracker,
+}
+
+impl<'a, const I: usize, const O: usize> Pop<'a, I, O> {
+    pub fn new(conf: Conf) -> Self {
+        let mut tracker = Tracker::default();
+
+        let genomes = tracker.within(|| {
+            (0..conf.pop_size).map(|id| Genome { lineage: Lineage::new(id, Vec::new(), 0), ..Genome::default() }).collect()
+        });
+
+        Self {
+            genomes,
+            tracker,
+            species: Vec::new(),
+            seed: conf.seed,
+            streams: 0,
+            next_species: 0,
+            next_genome: conf.pop_size,
+            conf,
+            generation: 0,
+        }
+    }
+
+    /// Returns the next random number generator of this population.
+    ///
+    /// Every generator is seeded from `conf.seed` and the number of generators handed out before
+    /// it, so a run started from the same seed draws the exact same numbers.
+    pub fn rng(&mut self) -> SmallRng {
+        let seed = self.seed.wrapping_add(self.streams.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        self.streams += 1;
+        SmallRng::seed_from_u64(seed)
+    }
+
+    // one seed per genome, so that each genome draws from its own stream no matter which genomes
+    // are evaluated before it or on which thread
+    fn seeds(&mut self) -> Vec<u64> {
+        let mut rng = self.rng();
+        self.genomes.iter().map(|_| rng.random()).collect()
+    }
+
+    /// Evaluates every genome, handing each of them a random number generator of its own.
+    pub fn evaluate(&mut self, eval: impl Fn(&Genome<'a, I, O>, &mut SmallRng) -> Fitness) {
+        let seeds = self.seeds();
+
+        for (genome, seed) in self.genomes.iter_mut().zip(seeds) {
+            genome.fitness = eval(genome, &mut SmallRng::seed_from_u64(seed));
+        }
+    }
+
+    /// Evaluates the genomes on up to `threads` threads at once. The nodes of a genome are shared
+    /// between its edges through `Cell`s, so every genome is compiled into a [`Network`] first and
+    /// only the networks are handed to other threads. Every network is evaluated on its own, so
+    /// the resulting fitnesses don't depend on the number of threads.
+    #[cfg(feature = "parallel")]
+    pub fn evaluate_parallel(&mut self, threads: usize, eval: impl Fn(&Network<I, O>, &mut SmallRng) -> Fitness + Sync) {
+        let chunk = self.genomes.len().div_ceil(threads.max(1)).max(1);
+        let seeds = self.seeds();
+        let networks = self.genomes.iter().map(Network::from).collect::<Vec<_>>();
+        let mut fitnesses = vec![Fitness::default(); networks.len()];
+        let eval = &eval;
+
+        std::thread::scope(|scope| {
+            for ((networks, seeds), fitnesses) in networks.chunks(chunk).zip(seeds.chunks(chunk)).zip(fitnesses.chunks_mut(chunk)) {
+                scope.spawn(move || {
+                    for ((network, seed), fitness) in networks.iter().zip(seeds).zip(fitnesses) {
+                        *fitness = eval(network, &mut SmallRng::seed_from_u64(*seed));
+                    }
+                });
+            }
+        });
+
+        for (genome, fitness) in self.genomes.iter_mut().zip(fitnesses) {
+            genome.fitness = fitness;
+        }
+    }
+
+    /// Evaluates every genome against several objectives at once and ranks them with
+    /// non-dominated sorting and crowding distance, so that the rest of the generation can treat
+    /// the rank as an ordinary fitness.
+    pub fn evaluate_multi<const N: usize>(
+        &mut self,
+        goals: &[Goal; N],
+        eval: impl Fn(&Genome<'a, I, O>, &mut SmallRng) -> Objectives<N>,
+    ) {
+        let seeds = self.seeds();
+
+        let objectives = self.genomes.iter().zip(seeds)
+            .map(|(genome, seed)| eval(genome, &mut SmallRng::seed_from_u64(seed)))
+            .collect::<Vec<_>>();
+
+        for (genome, fitness) in self.genomes.iter_mut().zip(Objectives::rank(&objectives, goals)) {
+            genome.fitness = fitness;
+        }
+    }
+
+    pub fn champion(&self) -> Option<&Genome<'a, I, O>> {
+        self.genomes.iter().max_by(|lhs, rhs| lhs.fitness.total_cmp(&rhs.fitness))
+    }
+
+    /// Assigns every genome to the first species whose representative it is compatible with,
+    /// founding a new species if there is none, and returns the species that were left without
+    /// any members.
+    pub fn speciate(&mut self) -> Vec<Species<'a, I, O>> {
+        for species in self.species.iter_mut() {
+            species.members.clear();
+        }
+
+        for (idx, genome) in self.genomes.iter().enumerate() {
+            match self.species.iter_mut().find(|species| genome.compat_dist(&species.repr, &self.conf) < self.conf.compat_threshold) {
+                Some(species) => species.members.push(idx),
+                None => {
+                    self.species.push(Species::new(self.next_species, genome.clone(), idx));
+                    self.next_species += 1;
+                }
+            }
+        }
+
+        let extinct = self.species.extract_if(.., |species| species.members.is_empty()).collect();
+
+        for species in self.species.iter_mut() {
+            species.update(&self.genomes);
+        }
+
+        extinct
+    }
+
+    /// Replaces the population with the next generation.
+    ///
+    /// Stagnant species are removed, unless one holds the champion, and every remaining species
+    /// is allotted a share of the offspring proportional to its adjusted fitness.
+    pub fn evolve(&mut self) {
+        self.evolve_with(&mut ());
+    }
+
+    /// Does the same as [`Pop::evolve`], telling `reporter` about the species as they change and
+    /// about the generation once it's been speciated, right before it's replaced.
+    pub fn evolve_with(&mut self, reporter: &mut impl Reporter<I, O>) {
+        let rng = &mut self.rng();
+        let mut extinct = self.speciate();
+
+        reporter.species_changed(&self.species);
+
+        let champion = self.genomes.iter().enumerate()
+            .max_by(|(_, lhs), (_, rhs)| lhs.fitness.total_cmp(&rhs.fitness))
+            .map(|(idx, _)| idx);
+
+        extinct.extend(self.species.extract_if(.., |species| {
+            species.stagnation > self.conf.max_stagnation && champion.is_none_or(|champion| !species.members.contains(&champion))
+        }));
+
+        for species in extinct.iter() {
+            reporter.extinction(species);
+        }
+
+        reporter.generation_end(self);
+
+        // the offspring are numbered by this population alone, and the thread gets its own
+        // innovation numbers back once they've been bred
+        Innov::swap(&mut self.tracker);
+
+        let min = self.genomes.iter().map(|genome| *genome.fitness).fold(f32::INFINITY, f32::min);
+
+        let adjusted = self.species.iter()
+            .map(|species| species.adjusted(&self.genomes, min))
+            .collect::<Vec<_>>();
+
+        let spawns = allot(&adjusted, self.conf.pop_size);
+        let mut next = Vec::with_capacity(self.conf.pop_size);
+
+        for (species, spawn) in self.species.iter_mut().zip(spawns) {
+            species.members.sort_by(|lhs, rhs| self.genomes[*rhs].fitness.total_cmp(&self.genomes[*lhs].fitness));
+
+            // a species can't hand down more elites than it has members
+            let elites = cmp::min(self.conf.elitism, spawn).min(species.members.len());
+
+            next.extend(species.members[..elites].iter().map(|idx| Genome {
+                parent_species: Some(species.id),
+                ..self.genomes[*idx].clone()
+            }));
+
+            let survivors = math::ceil(self.conf.survival_threshold * species.members.len() as f32) as usize;
+            let parents = &species.members[..survivors.clamp(1, species.members.len())];
+
+            for _ in elites..spawn {
+                let lhs = &self.genomes[*parents.choose(rng).unwrap()];
+                let rhs = &self.genomes[*parents.choose(rng).unwrap()];
+
+                let (mut child, lineage) = match ptr::eq(lhs, rhs) {
+                    false => (Genome::crossover(lhs, rhs, rng), vec![lhs.lineage.id, rhs.lineage.id]),
+                    true => (lhs.clone(), vec![lhs.lineage.id]),
+                };
+
+                child.lineage = Lineage::new(self.next_genome, lineage, self.generation + 1);
+                self.next_genome += 1;
+
+                child.mutate(&self.conf, rng);
+                child.parent_species = Some(species.id);
+                next.push(child);
+            }
+        }
+
+        Innov::swap(&mut self.tracker);
+
+        self.genomes = next;
+        self.generation += 1;
+    }
+
+    /// Evaluates and evolves the population for `generations` generations, telling `reporter`
+    /// about everything that happens along the way.
+    pub fn run(
+        &mut self,
+        generations: usize,
+        eval: impl Fn(&Genome<'a, I, O>, &mut SmallRng) -> Fitness,
+        reporter: &mut impl Reporter<I, O>,
+    ) {
+        let mut best = None::<Fitness>;
+
+        for _ in 0..generations {
+            reporter.generation_start(self.generation);
+            self.evaluate(&eval);
+
+            let champion = self.champion().unwrap();
+
+            if best.is_none_or(|best| champion.fitness > best) {
+                best = Some(champion.fitness);
+                reporter.new_best(champion);
+            }
+
+            self.evolve_with(reporter);
+        }
+
+        reporter.run_complete(self);
+    }
+
+    /// Runs a single replacement of real-time NEAT, as an alternative to [`Pop::evolve`] for
+    /// populations that are evaluated continuously.
+    ///
+    /// Ages every genome, then replaces the genome with the lowest adjusted fitness among those
+    /// at least `conf.min_age` old with an offspring of a species chosen with a probability
+    /// proportional to its average fitness. Only the offspring is speciated. The offspring starts
+    /// out with a default fitness, and its index is returned so that it can be put to work, or
+    /// nothing if no genome was old enough to be replaced.
+    pub fn replace(&mut self) -> Option<usize> {
+        let rng = &mut self.rng();
+
+        for genome in self.genomes.iter_mut() {
+            genome.age += 1;
+        }
+
+        if self.species.is_empty() {
+            self.speciate();
+        }
+
+        let (worst, species) = self.species.iter().enumerate()
+            .flat_map(|(idx, species)| species.members.iter().map(move |member| (*member, idx, species.members.len())))
+            .filter(|(member, ..)| self.genomes[*member].age >= self.conf.min_age)
+            .map(|(member, idx, len)| (member, idx, *self.genomes[member].fitness / len as f32))
+            .min_by(|(.., lhs), (.., rhs)| lhs.total_cmp(rhs))
+            .map(|(member, idx, _)| (member, idx))?;
+
+        self.species[species].members.retain(|idx| *idx != worst);
+        self.species.retain(|species| !species.members.is_empty());
+
+        let min = self.genomes.iter().map(|genome| *genome.fitness).fold(f32::INFINITY, f32::min);
+        let alone = [worst];
+
+        let species = self.species
+            .choose_weighted(rng, |species| species.adjusted(&self.genomes, min))
+            .ok()
+            .or_else(|| self.species.choose(rng));
+
+        // a genome that was the last of its species and of the population only has itself
+        let parents = species.map_or(&alone[..], |species| &species.members[..]);
+        let parent_species = species.map(|species| species.id);
+
+        let lhs = &self.genomes[*parents.choose(rng).unwrap()];
+        let rhs = &self.genomes[*parents.choose(rng).unwrap()];
+
+        Innov::swap(&mut self.tracker);
+
+        let (mut child, lineage) = match ptr::eq(lhs, rhs) {
+            false => (Genome::crossover(lhs, rhs, rng), vec![lhs.lineage.id, rhs.lineage.id]),
+            true => (lhs.clone(), vec![lhs.lineage.id]),
+        };
+
+        child.lineage = Lineage::new(self.next_genome, lineage, self.generation);
+        self.next_genome += 1;
+
+        child.mutate(&self.conf, rng);
+        Innov::swap(&mut self.tracker);
+        child.fitness = Fitness::default();
+        child.age = 0;
+        child.parent_species = parent_species;
+
+        match self.species.iter_mut().find(|species| child.compat_dist(&species.repr, &self.conf) < self.conf.compat_threshold) {
+            Some(species) => species.members.push(worst),
+            None => {
+                self.species.push(Species::new(self.next_species, child.clone(), worst));
+                self.next_species += 1;
+            }
+        }
+
+        self.genomes[worst] = child;
+        Some(worst)
+    }
+}
+
+// splits `total` offspring between species proportionally to their adjusted fitness, handing the
+// remainder left by rounding down to the species with the largest fractional shares
+fn allot(adjusted: &[f32], total: usize) -> Vec<usize> {
+    let sum = adjusted.iter().sum::<f32>();
+
+    let shares = adjusted.iter()
+        .map(|fitness| match sum > 0.0 {
+            false => total as f32 / adjusted.len() as f32,
+            true => fitness / sum * total as f32,
+        })
+        .collect::<Vec<_>>();
+
+    let mut spawns = shares.iter().map(|share| *share as usize).collect::<Vec<_>>();
+    let mut order = (0..shares.len()).collect::<Vec<_>>();
+    order.sort_by(|lhs, rhs| (shares[*rhs] - math::floor(shares[*rhs])).total_cmp(&(shares[*lhs] - math::floor(shares[*lhs]))));
+
+    let remainder = total.saturating_sub(spawns.iter().sum());
+
+    for idx in order.into_iter().cycle().take(if shares.is_empty() { 0 } else { remainder }) {
+        spawns[idx] += 1;
+    }
+
+    spawns
+}
@@ -0,0 +1,95 @@
+extern crate alloc;
+use crate::{genome::Genome, pop::Pop, species::Species};
+use core::{f32::math, fmt};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::{io, path::Path};
+
+/// Hooks into the run of a [`Pop`]. Every hook does nothing unless it's overridden.
+pub trait Reporter<const I: usize, const O: usize> {
+    fn generation_start(&mut self, generation: usize) {}
+
+    /// Called once the generation has been evaluated and speciated, right before it's replaced
+    /// by its offspring.
+    fn generation_end(&mut self, pop: &Pop<'_, I, O>) {}
+
+    /// Called after every speciation.
+    fn species_changed(&mut self, species: &[Species<'_, I, O>]) {}
+
+    /// Called whenever a generation's champion is fitter than every champion before it.
+    fn new_best(&mut self, genome: &Genome<'_, I, O>) {}
+
+    /// Called for every species that is left without members or removed for stagnating.
+    fn extinction(&mut self, species: &Species<'_, I, O>) {}
+
+    fn run_complete(&mut self, pop: &Pop<'_, I, O>) {}
+}
+
+impl<const I: usize, const O: usize> Reporter<I, O> for () {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenerationStats {
+    pub generation: usize,
+    pub best: f32,
+    pub mean: f32,
+    pub stdev: f32,
+    pub species: Vec<usize>,
+    pub mean_hiddens: f32,
+    pub mean_edges: f32,
+}
+
+/// A [`Reporter`] that records the statistics of every generation.
+#[derive(Clone, Debug, Default)]
+pub struct Statistics {
+    pub generations: Vec<GenerationStats>,
+}
+
+impl Statistics {
+    /// Formats the statistics as CSV with one row per generation. The sizes of the species are
+    /// joined by spaces into a single column.
+    pub fn csv(&self) -> impl fmt::Display + '_ {
+        fmt::from_fn(move |f| {
+            writeln!(f, "generation,best,mean,stdev,species,species_sizes,mean_hiddens,mean_edges")?;
+
+            for stats in self.generations.iter() {
+                write!(f, "{},{},{},{},{},", stats.generation, stats.best, stats.mean, stats.stdev, stats.species.len())?;
+
+                for (idx, size) in stats.species.iter().enumerate() {
+                    match idx {
+                        0 => write!(f, "{size}")?,
+                        _ => write!(f, " {size}")?,
+                    }
+                }
+
+                writeln!(f, ",{},{}", stats.mean_hiddens, stats.mean_edges)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.csv().to_string())
+    }
+}
+
+impl<const I: usize, const O: usize> Reporter<I, O> for Statistics {
+    fn generation_end(&mut self, pop: &Pop<'_, I, O>) {
+        let len = pop.genomes.len().max(1) as f32;
+        let mean = |value: fn(&Genome<'_, I, O>) -> f32| pop.genomes.iter().map(value).sum::<f32>() / len;
+
+        let fitness = mean(|genome| *genome.fitness);
+        let variance = pop.genomes.iter().map(|genome| (*genome.fitness - fitness) * (*genome.fitness - fitness)).sum::<f32>() / len;
+
+        self.generations.push(GenerationStats {
+            generation: pop.generation,
+            best: pop.champion().map_or(f32::NAN, |champion| *champion.fitness),
+            mean: fitness,
+            stdev: math::sqrt(variance),
+            species: pop.species.iter().map(|species| species.members.len()).collect(),
+            mean_hiddens: mean(|genome| genome.hiddens.len() as f32),
+            mean_edges: mean(|genome| genome.edges.len() as f32),
+        });
+    }
+}
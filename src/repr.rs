@@ -0,0 +1,300 @@
+extern crate alloc;
+use crate::{activ, edge::{Edge, Edges}, fitness::Fitness, genome::Genome, lineage::Lineage, node::*, pop::Innov};
+use core::{cell::Cell, error, fmt};
+use alloc::{boxed::Box, string::{String, ToString}, vec::Vec};
+use hashbrown::{HashMap, HashSet};
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A node of a [`GenomeRepr`].
+///
+/// Inputs only keep their innovation number and bias, and are saved with the `identity`
+/// activation and `sum` aggregation since that's how they behave.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeGene {
+    pub innov: usize,
+    pub bias: f32,
+    pub response: f32,
+    pub tau: f32,
+    pub activation: String,
+    pub aggregation: String,
+}
+
+/// An edge of a [`GenomeRepr`], pointing at its nodes by their innovation numbers.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EdgeGene {
+    pub tail: usize,
+    pub head: usize,
+    pub weight: f32,
+    pub enabled: bool,
+    pub innov: usize,
+    /// Whether the edge feeds back into an earlier step rather than into its head's layer.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub recurrent: bool,
+}
+
+/// A genome without any pointers, cells or function pointers, so that it can be saved and
+/// turned back into a working [`Genome`] later.
+///
+/// Nodes are listed inputs first, then outputs, then hidden nodes. Edges are listed by
+/// innovation number.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GenomeRepr {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub nodes: Vec<NodeGene>,
+    pub edges: Vec<EdgeGene>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReprError {
+    Inputs(usize),
+    Outputs(usize),
+    MissingNodes(usize),
+    DuplicateNode(usize),
+    DuplicateEdge(usize),
+    Misnumbered(usize),
+    UnknownActivation(String),
+    UnknownAggregation(String),
+    UnknownTail(usize),
+    UnknownHead(usize),
+    Cycle(usize),
+}
+
+impl fmt::Display for ReprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Inputs(inputs) => write!(f, "genome has {inputs} inputs"),
+            Self::Outputs(outputs) => write!(f, "genome has {outputs} outputs"),
+            Self::MissingNodes(nodes) => write!(f, "genome only has {nodes} nodes"),
+            Self::DuplicateNode(innov) => write!(f, "node {innov} appears more than once"),
+            Self::DuplicateEdge(innov) => write!(f, "edge {innov} repeats the innovation number or the nodes of another edge"),
+            Self::Misnumbered(innov) => write!(f, "node {innov} is out of place among the inputs and outputs"),
+            Self::UnknownActivation(name) => write!(f, "unknown activation function {name:?}"),
+            Self::UnknownAggregation(name) => write!(f, "unknown aggregation function {name:?}"),
+            Self::UnknownTail(innov) => write!(f, "edge tail {innov} isn't an input or hidden node"),
+            Self::UnknownHead(innov) => write!(f, "edge head {innov} isn't a hidden or output node"),
+            Self::Cycle(innov) => write!(f, "edge {innov} closes a cycle without being recurrent"),
+        }
+    }
+}
+
+impl error::Error for ReprError {}
+
+impl NodeGene {
+    fn new(node: &impl Node) -> Self {
+        Self {
+            innov: node.innov(),
+            bias: node.bias(),
+            response: node.response(),
+            tau: node.tau(),
+            activation: activ::activation_name(node.activation()).expect("unnamed activation").to_string(),
+            aggregation: activ::aggregation_name(node.aggregator()).expect("unnamed aggregation").to_string(),
+        }
+    }
+
+    fn activation(&self) -> Result<fn(f32) -> f32, ReprError> {
+        activ::activation(&self.activation).ok_or_else(|| ReprError::UnknownActivation(self.activation.clone()))
+    }
+
+    fn aggregation(&self) -> Result<fn(&[f32]) -> f32, ReprError> {
+        activ::aggregation(&self.aggregation).ok_or_else(|| ReprError::UnknownAggregation(self.aggregation.clone()))
+    }
+}
+
+impl From<&Edge<'_>> for EdgeGene {
+    fn from(edge: &Edge<'_>) -> Self {
+        Self {
+            tail: edge.tail.innov(),
+            head: edge.head.innov(),
+            weight: edge.weight,
+            enabled: edge.enabled.get(),
+            innov: edge.innov,
+            recurrent: edge.recurrent,
+        }
+    }
+}
+
+impl<const I: usize, const O: usize> From<&Genome<'_, I, O>> for GenomeRepr {
+    fn from(genome: &Genome<'_, I, O>) -> Self {
+        let inputs = genome.inputs.iter().map(|input| NodeGene {
+            innov: input.innov(),
+            bias: input.bias(),
+            response: 1.0,
+            tau: 1.0,
+            activation: "identity".to_string(),
+            aggregation: "sum".to_string(),
+        });
+
+        let nodes = inputs
+            .chain(genome.outputs.iter().map(NodeGene::new))
+            .chain(genome.hiddens.iter().map(NodeGene::new))
+            .collect();
+
+        let mut edges = genome.edges.iter().map(EdgeGene::from).collect::<Vec<_>>();
+
+        edges.sort_by_key(|edge| edge.innov);
+
+        Self { inputs: I, outputs: O, nodes, edges }
+    }
+}
+
+impl<const I: usize, const O: usize> TryFrom<&GenomeRepr> for Genome<'_, I, O> {
+    type Error = ReprError;
+
+    /// Rebuilds the genome that `repr` was made from.
+    ///
+    /// Layers aren't saved, so they're worked out again from the edges: every edge that isn't
+    /// recurrent places its head past its tail. The innovation numbers are kept as they are, and
+    /// the current thread is kept from handing any of them out again.
+    fn try_from(repr: &GenomeRepr) -> Result<Self, ReprError> {
+        match (repr.inputs == I, repr.outputs == O, repr.nodes.len() >= I + O) {
+            (false, ..) => return Err(ReprError::Inputs(repr.inputs)),
+            (_, false, _) => return Err(ReprError::Outputs(repr.outputs)),
+            (.., false) => return Err(ReprError::MissingNodes(repr.nodes.len())),
+            _ => {}
+        }
+
+        let mut seen = HashSet::new();
+
+        if let Some(node) = repr.nodes.iter().find(|node| !seen.insert(node.innov)) {
+            return Err(ReprError::DuplicateNode(node.innov));
+        }
+
+        let (mut innovs, mut pairs) = (HashSet::new(), HashSet::new());
+
+        // a genome keeps its edges in a set ordered by innovation number, and only ever connects
+        // two nodes once
+        if let Some(edge) = repr.edges.iter().find(|edge| {
+            let (innov, pair) = (innovs.insert(edge.innov), pairs.insert((edge.tail, edge.head)));
+            !innov || !pair
+        }) {
+            return Err(ReprError::DuplicateEdge(edge.innov));
+        }
+
+        // inputs and outputs are numbered by their position, which is all that lets genomes from
+        // different runs agree on them
+        if let Some((_, node)) = repr.nodes[..I + O].iter().enumerate().find(|(idx, node)| node.innov != *idx) {
+            return Err(ReprError::Misnumbered(node.innov));
+        }
+
+        let inputs = repr.nodes[..I].iter().map(Input::from_gene).collect::<Vec<_>>();
+
+        let outputs = repr.nodes[I..I + O].iter()
+            .map(|gene| Ok(Output::from_gene(gene, gene.activation()?, gene.aggregation()?)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut genome = Genome {
+            inputs: Box::try_from(inputs.into_boxed_slice()).unwrap(),
+            outputs: Box::try_from(outputs.into_boxed_slice()).unwrap(),
+            hiddens: Hiddens::default(),
+            edges: Edges::default(),
+            fitness: Fitness::default(),
+            age: 0,
+            parent_species: None,
+            lineage: Lineage::default(),
+        };
+
+        let mut tails = genome.inputs.iter()
+            .map(|input| (input.innov(), Tail::from(input.downgrade().upgrade())))
+            .collect::<HashMap<_, _>>();
+
+        let mut heads = genome.outputs.iter()
+            .map(|output| (output.innov(), Head::from(output.downgrade().upgrade())))
+            .collect::<HashMap<_, _>>();
+
+        for gene in repr.nodes[I + O..].iter() {
+            let hidden = genome.hiddens.push(Hidden::from_gene(gene, gene.activation()?, gene.aggregation()?));
+            tails.insert(hidden.innov(), Tail::from(hidden));
+            heads.insert(hidden.innov(), Head::from(hidden));
+        }
+
+        let mut genes = repr.edges.iter().collect::<Vec<_>>();
+        genes.sort_by_key(|gene| gene.innov);
+
+        for gene in genes.iter() {
+            tails.get(&gene.tail).ok_or(ReprError::UnknownTail(gene.tail))?;
+            heads.get(&gene.head).ok_or(ReprError::UnknownHead(gene.head))?;
+        }
+
+        // mutations only ever push a head past the tail of the edge being added, so the layers
+        // are settled all at once here instead
+        let forward = genes.iter().copied().filter(|gene| !gene.recurrent).collect::<Vec<_>>();
+        let mut layers = HashMap::<usize, usize>::new();
+        let layer = |layers: &HashMap<_, _>, innov| match tails[&innov] {
+            Tail::Input(_) => 0,
+            Tail::Hidden(_) => layers.get(&innov).copied().unwrap_or(1),
+        };
+
+        let unsettled = |layers: &HashMap<_, _>, gene: &EdgeGene| {
+            layers.get(&gene.head).copied().unwrap_or(1) <= layer(layers, gene.tail)
+        };
+
+        // every pass settles the next edge along each path, and an acyclic path can't have more
+        // edges than there are in total
+        for _ in 0..=forward.len() {
+            let mut moved = false;
+
+            for gene in forward.iter() {
+                if unsettled(&layers, gene) {
+                    layers.insert(gene.head, layer(&layers, gene.tail) + 1);
+                    moved = true;
+                }
+            }
+
+            if !moved {
+                break;
+            }
+        }
+
+        if let Some(gene) = forward.iter().find(|gene| unsettled(&layers, gene)) {
+            return Err(ReprError::Cycle(gene.innov));
+        }
+
+        for (innov, layer) in layers.iter() {
+            heads[innov].update_layer(*layer);
+        }
+
+        for gene in genes {
+            let (tail, head) = (tails[&gene.tail].clone(), heads[&gene.head].clone());
+
+            genome.edges.insert(Edge {
+                layer: tail.layer(),
+                enabled: Cell::new(gene.enabled),
+                weight: gene.weight,
+                innov: gene.innov,
+                recurrent: gene.recurrent,
+                tail,
+                head,
+            });
+        }
+
+        if let Some(innov) = repr.nodes.iter().map(|node| node.innov).max() {
+            Innov::reserve_node_innov(innov);
+        }
+
+        for gene in repr.edges.iter() {
+            Innov::reserve_edge_innov(gene.tail, gene.head, gene.innov);
+        }
+
+        Ok(genome)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const I: usize, const O: usize> Serialize for Genome<'_, I, O> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GenomeRepr::from(self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const I: usize, const O: usize> Deserialize<'de> for Genome<'_, I, O> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = GenomeRepr::deserialize(deserializer)?;
+        Genome::try_from(&repr).map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,115 @@
+extern crate alloc;
+use crate::{pop::Pop, report::Reporter};
+use core::fmt;
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::{io, path::Path};
+
+const WIDTH: f32 = 800.0;
+const HEIGHT: f32 = 400.0;
+const MARGIN: f32 = 40.0;
+
+/// A [`Reporter`] that records how species arise, grow and die out.
+#[derive(Clone, Debug, Default)]
+pub struct SpeciesHistory {
+    /// Id and size of every species alive in each generation.
+    pub generations: Vec<Vec<(usize, usize)>>,
+    /// Id of every species that was ever alive along with the id of the species that bred its
+    /// founder, in the order they appeared.
+    pub parents: Vec<(usize, Option<usize>)>,
+}
+
+impl SpeciesHistory {
+    fn size(&self, generation: usize, id: usize) -> usize {
+        self.generations[generation].iter().find(|(curr, _)| *curr == id).map_or(0, |(_, size)| *size)
+    }
+
+    /// Formats the history as CSV with one row per species alive in each generation.
+    pub fn csv(&self) -> impl fmt::Display + '_ {
+        fmt::from_fn(move |f| {
+            writeln!(f, "generation,species,parent,size")?;
+
+            for (generation, species) in self.generations.iter().enumerate() {
+                for (id, size) in species.iter() {
+                    let parent = self.parents.iter().find(|(curr, _)| curr == id).and_then(|(_, parent)| *parent);
+
+                    match parent {
+                        Some(parent) => writeln!(f, "{generation},{id},{parent},{size}")?,
+                        None => writeln!(f, "{generation},{id},,{size}")?,
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Renders the history as a stacked area plot, with the generations running from left to
+    /// right and every species stacked on top of those that appeared before it.
+    pub fn svg(&self) -> impl fmt::Display + '_ {
+        fmt::from_fn(move |f| {
+            let generations = self.generations.len();
+            let total = self.generations.iter()
+                .map(|species| species.iter().map(|(_, size)| size).sum::<usize>())
+                .max()
+                .unwrap_or(0)
+                .max(1);
+
+            let x = |generation: usize| MARGIN + (generation as f32 * (WIDTH - (2.0 * MARGIN)) / generations.saturating_sub(1).max(1) as f32);
+            let y = |size: usize| HEIGHT - MARGIN - (size as f32 * (HEIGHT - (2.0 * MARGIN)) / total as f32);
+
+            writeln!(f, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}">"#)?;
+
+            let mut below = vec![0; generations];
+
+            for (id, _) in self.parents.iter() {
+                let above = (0..generations).map(|generation| below[generation] + self.size(generation, *id)).collect::<Vec<_>>();
+
+                // the upper edge from left to right, then the lower edge back again
+                write!(f, r#"  <polygon fill="hsl({}, 60%, 60%)" stroke="none" points=""#, (id * 137) % 360)?;
+
+                for (generation, size) in above.iter().enumerate() {
+                    write!(f, "{},{} ", x(generation), y(*size))?;
+                }
+
+                for (generation, size) in below.iter().enumerate().rev() {
+                    write!(f, "{},{} ", x(generation), y(*size))?;
+                }
+
+                writeln!(f, r#""><title>species {id}</title></polygon>"#)?;
+                below = above;
+            }
+
+            let (left, right, bottom) = (x(0), x(generations.saturating_sub(1)), y(0));
+            writeln!(f, r#"  <line x1="{left}" y1="{bottom}" x2="{right}" y2="{bottom}" stroke="black"/>"#)?;
+            writeln!(f, r#"  <line x1="{left}" y1="{bottom}" x2="{left}" y2="{}" stroke="black"/>"#, y(total))?;
+            writeln!(f, r#"  <text x="{}" y="{}" text-anchor="middle" font-size="12">generations</text>"#, WIDTH / 2.0, HEIGHT - (MARGIN / 3.0))?;
+            writeln!(f, r#"  <text x="{}" y="{}" text-anchor="middle" font-size="12" transform="rotate(-90 {0} {1})">size</text>"#, MARGIN / 2.0, HEIGHT / 2.0)?;
+            writeln!(f, "</svg>")
+        })
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.csv().to_string())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write_svg(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.svg().to_string())
+    }
+}
+
+impl<const I: usize, const O: usize> Reporter<I, O> for SpeciesHistory {
+    fn generation_end(&mut self, pop: &Pop<'_, I, O>) {
+        // ids are handed out in increasing order, so any species with a larger id than the last
+        // one seen has only just appeared
+        for species in pop.species.iter() {
+            if self.parents.last().is_none_or(|(last, _)| species.id > *last) {
+                self.parents.push((species.id, species.parent));
+            }
+        }
+
+        self.generations.push(pop.species.iter().map(|species| (species.id, species.members.len())).collect());
+    }
+}
@@ -0,0 +1,51 @@
+extern crate alloc;
+use crate::{fitness::Fitness, genome::Genome};
+use alloc::{vec, vec::Vec};
+
+#[derive(Debug)]
+pub struct Species<'a, const I: usize, const O: usize> {
+    pub id: usize,
+    /// Id of the species that bred the genome this species was founded by.
+    pub parent: Option<usize>,
+    pub repr: Genome<'a, I, O>,
+    /// Indices of the genomes of the current generation that belong to this species.
+    pub members: Vec<usize>,
+    pub best: Fitness,
+    /// Generations since `best` last improved.
+    pub stagnation: usize,
+}
+
+impl<'a, const I: usize, const O: usize> Species<'a, I, O> {
+    pub fn new(id: usize, repr: Genome<'a, I, O>, member: usize) -> Self {
+        Self {
+            id,
+            parent: repr.parent_species,
+            best: Fitness::from(f32::NEG_INFINITY),
+            repr,
+            members: vec![member],
+            stagnation: 0,
+        }
+    }
+
+    pub fn update(&mut self, genomes: &[Genome<'a, I, O>]) {
+        let best = self.members.iter()
+            .map(|idx| genomes[*idx].fitness)
+            .max_by(Fitness::total_cmp)
+            .unwrap_or_default();
+
+        match best > self.best {
+            false => self.stagnation += 1,
+            true => {
+                self.best = best;
+                self.stagnation = 0;
+            }
+        }
+    }
+
+    /// Sum of the explicitly shared fitness of every member, which is their mean fitness
+    /// measured from `min` so that it's never negative.
+    pub fn adjusted(&self, genomes: &[Genome<'a, I, O>], min: f32) -> f32 {
+        let sum = self.members.iter().map(|idx| *genomes[*idx].fitness - min).sum::<f32>();
+        sum / (self.members.len() as f32)
+    }
+}
@@ -0,0 +1,96 @@
+extern crate alloc;
+use crate::{dot::name, genome::Genome, node::*};
+use core::fmt;
+use alloc::vec::Vec;
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::{io, path::Path};
+
+const MARGIN: f32 = 40.0;
+const COLUMN: f32 = 120.0;
+const ROW: f32 = 60.0;
+const RADIUS: f32 = 16.0;
+
+impl<const I: usize, const O: usize> Genome<'_, I, O> {
+    /// Renders the genome as an SVG image, with one column per layer.
+    ///
+    /// Edges follow the same conventions as [`Genome::dot`]: green for positive weights, red for
+    /// negative ones, thicker the larger the weight, and dashed when disabled.
+    pub fn svg(&self) -> impl fmt::Display + '_ {
+        fmt::from_fn(move |f| {
+            let mut nodes = self.inputs.iter()
+                .map(|input| (0, input.innov(), input.bias(), "input", "#dddddd"))
+                .chain(self.hiddens.iter().map(|hidden| (hidden.layer(), hidden.innov(), hidden.bias(), name(hidden), "#ffffff")))
+                .chain(self.outputs.iter().map(|output| (output.layer(), output.innov(), output.bias(), name(output), "#ffcc88")))
+                .collect::<Vec<_>>();
+
+            nodes.sort_by_key(|(layer, innov, ..)| (*layer, *innov));
+
+            let columns = nodes.chunk_by(|lhs, rhs| lhs.0 == rhs.0).collect::<Vec<_>>();
+            let layers = nodes.last().map_or(0, |(layer, ..)| *layer);
+            let rows = columns.iter().map(|column| column.len()).max().unwrap_or(1);
+
+            let width = (2.0 * MARGIN) + (layers as f32 * COLUMN);
+            let height = (2.0 * MARGIN) + ((rows - 1) as f32 * ROW);
+
+            // every column is centred vertically
+            let mut points = HashMap::new();
+
+            for column in columns.iter() {
+                let top = (height - ((column.len() - 1) as f32 * ROW)) / 2.0;
+
+                for (row, (layer, innov, ..)) in column.iter().enumerate() {
+                    points.insert(*innov, (MARGIN + (*layer as f32 * COLUMN), top + (row as f32 * ROW)));
+                }
+            }
+
+            writeln!(f, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#)?;
+
+            for edge in self.edges.iter() {
+                let (x1, y1) = points[&edge.tail.innov()];
+                let (x2, y2) = points[&edge.head.innov()];
+
+                let color = match edge.weight < 0.0 { false => "forestgreen", true => "firebrick" };
+                let width = 0.5 + f32::min(edge.weight.abs(), 5.0);
+                let dash = match edge.enabled.get() { false => r#" stroke-dasharray="4 3""#, true => "" };
+
+                // a node feeding into itself gets a loop above it
+                let tag = match edge.tail.innov() == edge.head.innov() {
+                    false => {
+                        write!(f, r#"  <line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}""#)?;
+                        "line"
+                    }
+                    true => {
+                        write!(
+                            f,
+                            r#"  <path d="M {} {y1} C {} {} {} {} {} {y1}" fill="none""#,
+                            x1 - (RADIUS / 2.0),
+                            x1 - (2.0 * RADIUS),
+                            y1 - (3.0 * RADIUS),
+                            x1 + (2.0 * RADIUS),
+                            y1 - (3.0 * RADIUS),
+                            x1 + (RADIUS / 2.0),
+                        )?;
+                        "path"
+                    }
+                };
+
+                writeln!(f, r#" stroke="{color}" stroke-width="{width:.2}"{dash}><title>{:.3}</title></{tag}>"#, edge.weight)?;
+            }
+
+            for (_, innov, bias, activ, fill) in nodes.iter() {
+                let (x, y) = points[innov];
+                writeln!(f, r#"  <circle cx="{x}" cy="{y}" r="{RADIUS}" fill="{fill}" stroke="black"><title>bias {bias:.3}, {activ}</title></circle>"#)?;
+                writeln!(f, r#"  <text x="{x}" y="{y}" text-anchor="middle" dominant-baseline="central" font-size="12">{innov}</text>"#)?;
+            }
+
+            writeln!(f, "</svg>")
+        })
+    }
+
+    /// Writes [`Genome::svg`] to the file at `path`.
+    #[cfg(feature = "std")]
+    pub fn write_svg(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.svg().to_string())
+    }
+}
@@ -1,5 +1,9 @@
-use crate::genome::Genome;
-use rand::{rngs::SmallRng, SeedableRng};
+use crate::{activ::{self, AGGREGATIONS}, checkpoint::Checkpoint, codec::*, coevo::*, conf::Conf, ctrnn::Ctrnn, edge::Edge, elites::Elites, fitness::*, es::EvolvableSubstrate, genome::{AddEdgeError, Genome}, hyper::Substrate, islands::*, lineage::*, network::*, node::Node, novelty::Novelty, pop::*, report::*, repr::*, speciation::SpeciesHistory, validate::Violation};
+use core::{cell::Cell, ptr};
+use hashbrown::{HashMap, HashSet};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+extern crate test;
 
 fn mutate_split_edge<const I: usize, const O: usize>(
     genome: &mut Genome<I, O>,
@@ -8,25 +12,1027 @@ fn mutate_split_edge<const I: usize, const O: usize>(
     for seed in 0..u8::MAX {
         let mut clone = genome.clone();
         let mut rng = SmallRng::seed_from_u64(seed as u64);
-        clone.mutate_split_edge(&mut rng);
+        clone.mutate_split_edge(&Conf::default(), &mut rng);
         if assert(&clone) {
             let mut rng = SmallRng::seed_from_u64(seed as u64);
-            genome.mutate_split_edge(&mut rng);
+            genome.mutate_split_edge(&Conf::default(), &mut rng);
             return;
         }
     };
     panic!("could not find seed that validates the assertion after {} attempts", u8::MAX);
 }
 
+fn grow<const I: usize, const O: usize>(genome: &mut Genome<I, O>, rng: &mut impl Rng, steps: usize) {
+    for _ in 0..steps {
+        _ = genome.mutate_add_edge(&Conf::default(), rng);
+        genome.mutate_split_edge(&Conf::default(), rng);
+    }
+}
+
 #[test]
 fn it_works() {
     let mut genome = Genome::<1, 1>::default();
 
     let mut rng = SmallRng::seed_from_u64(0);
-    genome.mutate_add_edge(&mut rng);
+    _ = genome.mutate_add_edge(&Conf::default(), &mut rng);
 
     mutate_split_edge(&mut genome, |_| true);
 
     dbg!(&genome.hiddens);
 }
 
+#[test]
+fn network_matches_genome() {
+    let mut genome = Genome::<3, 2>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 4);
+
+    let network = Network::from(&genome);
+    let rows = (0..20).map(|_| rng.random::<[f32; 3]>()).collect::<Vec<_>>();
+    let mut outputs = vec![[0.0; 2]; rows.len()];
+    network.activate_batch(&rows, &mut outputs);
+
+    for (row, output) in rows.iter().zip(&outputs) {
+        assert_eq!(genome.activate(*row), network.activate(*row));
+        assert_eq!(network.activate(*row), *output);
+    }
+}
+
+#[test]
+fn batch_matches_every_aggregation() {
+    let mut genome = Genome::<3, 2>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 4);
+
+    let rows = (0..20).map(|_| rng.random::<[f32; 3]>()).collect::<Vec<_>>();
+
+    for (name, _) in AGGREGATIONS {
+        let mut repr = GenomeRepr::from(&genome);
+
+        for node in repr.nodes.iter_mut() {
+            node.aggregation = name.to_string();
+        }
+
+        let network = Network::from(&Genome::<3, 2>::try_from(&repr).unwrap());
+        let mut outputs = vec![[0.0; 2]; rows.len()];
+        network.activate_batch(&rows, &mut outputs);
+
+        for (row, output) in rows.iter().zip(&outputs) {
+            assert_eq!(network.activate(*row), *output);
+        }
+    }
+}
+
+#[test]
+fn recurrent_network_reset() {
+    let conf = Conf { recurrent: true, ..Conf::default() };
+    let mut genome = Genome::<2, 1>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    for _ in 0..6 {
+        _ = genome.mutate_add_edge(&conf, &mut rng);
+        genome.mutate_split_edge(&conf, &mut rng);
+    }
+
+    let mut network = RecurrentNetwork::from(&genome);
+    let first = network.activate([1.0, -1.0]);
+    network.activate([0.5, 0.5]);
+    network.reset();
+
+    assert_eq!(network.activate([1.0, -1.0]), first);
+}
+
+#[test]
+fn recurrent_network_reads_back_edges_from_the_previous_step() {
+    let conf = Conf { recurrent: true, ..Conf::default() };
+    let mut genome = Genome::<1, 1>::default();
+    genome.edges.insert(Edge::new(&genome.inputs[0], &genome.outputs[0]));
+
+    // input -> first -> second -> output, with the second feeding back into the first
+    let (input, rest) = genome.hiddens.split_edge(genome.edges.iter().next().unwrap());
+    genome.edges.insert(Edge { weight: 2.0, ..input });
+
+    let (forward, output) = genome.hiddens.split_edge(&rest);
+    genome.edges.insert(rest);
+
+    let first = forward.tail.hidden().unwrap().downgrade().upgrade();
+    let second = forward.head.hidden().unwrap().downgrade().upgrade();
+    genome.edges.insert(Edge { weight: 0.5, ..Edge::recurrent(second, first) });
+    genome.edges.insert(forward);
+    genome.edges.insert(output);
+    assert_eq!(genome.validate(&conf), vec![]);
+
+    // hidden nodes take the mean of their inputs, so the first node sees (2x + h) / 2 where h is
+    // half of what the second node held at the end of the step before
+    let mut network = RecurrentNetwork::from(&genome);
+    assert_eq!(network.activate([1.0]), [1.0]);
+    assert_eq!(network.activate([1.0]), [1.25]);
+    assert_eq!(network.activate([0.0]), [0.3125]);
+
+    // without the previous step to read from, the back edge contributes nothing
+    assert_eq!(Network::from(&genome).activate([1.0]), [1.0]);
+}
+
+#[test]
+fn network_order_ignores_stale_layers() {
+    let conf = Conf { recurrent: true, ..Conf::default() };
+    let mut genome = Genome::<1, 1>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    _ = genome.mutate_add_edge(&conf, &mut rng);
+    genome.mutate_split_edge(&conf, &mut rng);
+    let expected = Network::from(&genome).activate([1.0]);
+
+    // leaves the hidden node past the output that it feeds
+    genome.hiddens.iter().next().unwrap().update_layer(5);
+
+    assert_eq!(Network::from(&genome).activate([1.0]), expected);
+    assert_eq!(RecurrentNetwork::from(&genome).activate([1.0]), expected);
+}
+
+#[test]
+fn mutate_add_edge_stays_acyclic() {
+    let conf = Conf::default();
+    let mut genome = Genome::<2, 2>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    for _ in 0..32 {
+        _ = genome.mutate_add_edge(&conf, &mut rng);
+        genome.mutate_split_edge(&conf, &mut rng);
+    }
+
+    for edge in genome.edges.iter() {
+        assert!(!genome.edges.reaches(&edge.head, &edge.tail));
+    }
+}
+
+#[test]
+fn mutations_keep_layers_settled() {
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    for conf in [Conf::default(), Conf { recurrent: true, ..Conf::default() }] {
+        let mut genome = Genome::<2, 2>::default();
+
+        for _ in 0..32 {
+            _ = genome.mutate_add_edge(&conf, &mut rng);
+            genome.mutate_split_edge(&conf, &mut rng);
+
+            for edge in genome.edges.iter().filter(|edge| !edge.recurrent) {
+                assert!(edge.head.layer() > edge.tail.layer());
+            }
+        }
+    }
+}
+
+#[test]
+fn mutate_activation_round_trips() {
+    let conf = Conf::default();
+    let mut genome = Genome::<2, 2>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 4);
+
+    for _ in 0..32 {
+        genome.mutate_activation(&conf, &mut rng);
+    }
+
+    let repr = GenomeRepr::from(&genome);
+    let names = repr.nodes[2..].iter().map(|node| node.activation.as_str()).collect::<HashSet<_>>();
+    assert!(names.len() > 2);
+
+    assert_eq!(GenomeRepr::from(&Genome::<2, 2>::try_from(&repr).unwrap()), repr);
+    assert_eq!(GenomeRepr::decode(&repr.encode().unwrap()).unwrap(), repr);
+}
+
+#[test]
+fn mutate_add_edge_saturates() {
+    let conf = Conf::default();
+    let mut genome = Genome::<1, 1>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    assert_eq!(genome.mutate_add_edge(&conf, &mut rng), Ok(()));
+    assert_eq!(genome.mutate_add_edge(&conf, &mut rng), Err(AddEdgeError::Saturated));
+    assert_eq!(genome.edges.len(), 1);
+
+    // input -> first -> second -> output, where only the cycle closing pair is left unconnected
+    mutate_split_edge(&mut genome, |_| true);
+    mutate_split_edge(&mut genome, |_| true);
+    while genome.mutate_add_edge(&conf, &mut rng).is_ok() {}
+
+    let edges = genome.edges.len();
+    assert_eq!(genome.mutate_add_edge(&conf, &mut rng), Err(AddEdgeError::Saturated));
+    assert_eq!(genome.edges.len(), edges);
+
+    let conf = Conf { add_edge_attempts: 0, ..conf };
+    let mut genome = Genome::<1, 1>::default();
+    assert_eq!(genome.mutate_add_edge(&conf, &mut rng), Err(AddEdgeError::Exhausted));
+    assert_eq!(genome.edges.len(), 0);
+}
+
+#[test]
+fn ctrnn_settles() {
+    let mut genome = Genome::<1, 1>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    _ = genome.mutate_add_edge(&Conf::default(), &mut rng);
+
+    let mut network = Ctrnn::from(&genome);
+    let [output] = network.advance([0.5], 0.1, 200);
+
+    assert!((output - 0.5).abs() < 1e-3);
+}
+
+#[test]
+fn substrate_build() {
+    // paints weights that fall off with the horizontal distance between the tail and the head,
+    // and only expresses the connections that run left to right
+    let mut cppn = Genome::<4, 2>::default();
+
+    for (output, weights) in cppn.outputs.iter().zip([[2.0, -2.0], [-2.0, 2.0]]) {
+        for (input, weight) in [&cppn.inputs[0], &cppn.inputs[2]].into_iter().zip(weights) {
+            let edge = Edge { weight, ..Edge::new(input.downgrade().upgrade(), output.downgrade().upgrade()) };
+            cppn.edges.insert(edge);
+        }
+    }
+
+    cppn.outputs[0].update_activation(activ::gaussian);
+    cppn.outputs[1].update_activation(activ::sine);
+
+    let mut substrate = Substrate::new([[-1.0, -1.0], [1.0, -1.0]], [[0.0, 1.0]]);
+    substrate.hiddens.push(vec![[-0.5, 0.0], [0.0, 0.0], [0.5, 0.0]]);
+
+    let conns = |network: &Network<2, 1>| network.neurons.iter()
+        .map(|neuron| network.conns[neuron.conns.clone()].iter().map(|conn| (conn.tail, conn.weight)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let scaled = |dist: f32| (activ::gaussian(dist) - substrate.threshold) / (1.0 - substrate.threshold) * substrate.max_weight;
+
+    // a tail more than a unit away from the head isn't expressed
+    let network = substrate.build(&cppn);
+    assert_eq!(conns(&network), [
+        vec![(0, scaled(0.5))],
+        vec![(0, scaled(1.0)), (1, scaled(1.0))],
+        vec![(1, scaled(0.5))],
+        vec![(2, scaled(0.5)), (3, scaled(0.0)), (4, scaled(0.5))],
+    ]);
+
+    let leo = |dist: f32| activ::gaussian(dist) * substrate.max_weight;
+
+    substrate.leo = true;
+    let network = substrate.build(&cppn);
+    assert_eq!(conns(&network), [
+        vec![(0, leo(0.5))],
+        vec![(0, leo(1.0))],
+        vec![(0, leo(1.5))],
+        vec![(2, leo(0.5))],
+    ]);
+}
+
+#[test]
+fn evolvable_substrate_build() {
+    let mut cppn = Genome::<4, 1>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut cppn, &mut rng, 8);
+
+    let substrate = EvolvableSubstrate::new([[-1.0, -1.0], [0.0, -1.0], [1.0, -1.0]], [[-0.5, 1.0], [0.5, 1.0]]);
+    let network = substrate.build(&cppn);
+
+    for conn in network.conns.iter() {
+        assert!(conn.tail < 3 + network.neurons.len() - 2);
+    }
+
+    assert!(network.activate([1.0, 0.0, -1.0]).iter().all(|output| output.is_finite()));
+}
+
+#[test]
+fn novelty_archive() {
+    let mut novelty = Novelty::new(1.5);
+    novelty.k = 1;
+
+    let scores = novelty.score(&[vec![0.0], vec![1.0], vec![3.0]]);
+    assert_eq!(scores, [1.0, 1.0, 2.0]);
+    assert_eq!(novelty.archive(), [vec![3.0]]);
+
+    // the archived behavior is now the nearest neighbour of the first one
+    let scores = novelty.score(&[vec![3.5], vec![10.0]]);
+    assert_eq!(scores, [0.5, 6.5]);
+    assert_eq!(novelty.archive().len(), 2);
+}
+
+#[test]
+fn objectives_rank() {
+    let goals = [Goal::Maximize, Goal::Minimize];
+    let points = [
+        Objectives([1.0, 5.0]),
+        Objectives([2.0, 2.0]),
+        Objectives([1.0, 6.0]),
+        Objectives([3.0, 4.0]),
+        Objectives([0.5, 1.0]),
+    ];
+
+    assert_eq!(Objectives::fronts(&points, &goals), [vec![1, 3, 4], vec![0], vec![2]]);
+
+    let fitnesses = Objectives::rank(&points, &goals);
+    assert!(fitnesses[4] > fitnesses[0] && fitnesses[0] > fitnesses[2]);
+    assert_eq!(*fitnesses[1], 3.0 + (0.5 * 2.0 / 3.0));
+}
+
+#[test]
+fn elites_keep_fittest() {
+    let mut elites = Elites::<1, 1, 2>::new(Conf::default(), [4, 2], [(0.0, 1.0), (-1.0, 1.0)]);
+    assert_eq!(elites.cell([0.3, 0.5]), [1, 1]);
+    assert_eq!(elites.cell([2.0, -3.0]), [3, 0]);
+
+    let genome = Genome { fitness: Fitness::from(1.0), ..Genome::default() };
+    assert!(elites.insert(genome, [0.3, 0.5]));
+
+    let genome = Genome { fitness: Fitness::from(0.5), ..Genome::default() };
+    assert!(!elites.insert(genome, [0.4, 0.9]));
+
+    assert_eq!(elites.coverage(), 1.0 / 8.0);
+    assert_eq!(elites.qd_score(), 1.0);
+}
+
+#[test]
+fn elites_children_get_fresh_ids() {
+    let conf = Conf { add_edge_prob: 0.5, split_edge_prob: 0.3, ..Conf::default() };
+    let mut elites = Elites::<1, 1, 1>::new(conf, [8], [(0.0, 1.0)]);
+    let mut rng = SmallRng::seed_from_u64(0);
+    elites.crossover_prob = 0.5;
+
+    for _ in 0..10 {
+        elites.step(4, &mut rng, |genome| (Fitness::from(genome.activate([0.5])[0]), [genome.edges.len() as f32 / 8.0]));
+    }
+
+    let mut ids = HashSet::new();
+    assert!(elites.coverage() > 0.25);
+
+    for (_, elite) in elites.iter() {
+        assert!(ids.insert(elite.lineage.id));
+        assert!(elite.lineage.parents.iter().all(|parent| *parent < elite.lineage.id));
+        assert_eq!(elite.lineage.born, elite.lineage.id / 4);
+    }
+}
+
+#[test]
+fn coevo_shares_wins() {
+    let players = vec![Network::from(&Genome::<1, 1>::default()); 3];
+    let opponents = vec![Network::from(&Genome::<1, 1>::default()); 2];
+    let wins = [[true, true], [true, false], [false, false]];
+
+    let fitness = share(&players, &opponents, |player, opponent| {
+        let player = players.iter().position(|curr| ptr::eq(curr, player)).unwrap();
+        let opponent = opponents.iter().position(|curr| ptr::eq(curr, opponent)).unwrap();
+        wins[player][opponent]
+    });
+
+    // the first opponent was beaten twice and the second only once
+    assert_eq!(fitness, [Fitness::from(1.5), Fitness::from(0.5), Fitness::from(0.0)]);
+}
+
+#[test]
+fn coevo_halls_grow_and_are_played() {
+    let mut coevo = Coevo::<1, 1>::new(Conf { pop_size: 10, ..Conf::default() });
+    coevo.opponents = 3;
+    coevo.hall_opponents = 2;
+
+    let games = Cell::new(0);
+    let play = |host: &Network<1, 1>, parasite: &Network<1, 1>| {
+        games.set(games.get() + 1);
+        (host.activate([1.0])[0], parasite.activate([-1.0])[0])
+    };
+
+    // both sides play their sample of the current generation, and as much of the other side's
+    // hall of fame as there is
+    for (step, opponents) in [3, 4, 5, 5].into_iter().enumerate() {
+        games.set(0);
+        coevo.step(play);
+
+        assert_eq!(games.get(), 2 * 10 * opponents);
+        assert_eq!((coevo.host_hall.len(), coevo.parasite_hall.len()), (step + 1, step + 1));
+    }
+}
+
+#[test]
+fn innov_swap() {
+    let before = Innov::next_node_innov();
+    let mut tracker = Tracker::default();
+
+    Innov::swap(&mut tracker);
+    assert_eq!(Innov::next_node_innov(), 0);
+    assert_eq!(Innov::next_node_innov(), 1);
+    Innov::swap(&mut tracker);
+
+    assert_eq!(Innov::next_node_innov(), before + 1);
+
+    Innov::swap(&mut tracker);
+    assert_eq!(Innov::next_node_innov(), 2);
+    Innov::swap(&mut tracker);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn evaluate_parallel() {
+    let eval = |network: &Network<2, 1>, rng: &mut SmallRng| {
+        let [output] = network.activate(rng.random());
+        Fitness::from(output)
+    };
+
+    let pop = || {
+        let mut pop = Pop::<2, 1>::new(Conf { pop_size: 37, ..Conf::default() });
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        // gives each genome its own weights, so that the fitnesses tell the genomes apart
+        pop.tracker.within(|| {
+            for genome in pop.genomes.iter_mut() {
+                grow(genome, &mut rng, 1);
+                genome.mutate_weight(&Conf::default(), &mut rng);
+            }
+        });
+
+        pop
+    };
+
+    let mut serial = pop();
+    serial.evaluate(|genome, rng| eval(&Network::from(genome), rng));
+
+    for threads in [1, 3, 8, 64] {
+        let mut parallel = pop();
+        parallel.evaluate_parallel(threads, eval);
+        assert!(parallel.genomes.iter().map(|genome| genome.fitness).eq(serial.genomes.iter().map(|genome| genome.fitness)));
+    }
+}
+
+// islands that number their own innovations, each grown apart and given fitnesses that tell every
+// genome of every island apart
+fn islands(topology: Topology) -> Islands<'static, 2, 1> {
+    let mut islands = Islands::<2, 1>::new(Conf { pop_size: 10, ..Conf::default() }, 3, false);
+    let mut rng = SmallRng::seed_from_u64(0);
+    islands.topology = topology;
+
+    for (island, pop) in islands.pops.iter_mut().enumerate() {
+        pop.tracker.within(|| {
+            for (idx, genome) in pop.genomes.iter_mut().enumerate() {
+                grow(genome, &mut rng, 3);
+                genome.mutate_weight(&Conf::default(), &mut rng);
+                genome.fitness = Fitness::from((island * 100 + idx) as f32);
+            }
+        });
+    }
+
+    islands
+}
+
+fn check_migration(topology: Topology, arrivals: [&[f32]; 3]) {
+    let mut islands = islands(topology);
+
+    let outputs = islands.pops.iter()
+        .flat_map(|pop| pop.genomes.iter())
+        .map(|genome| (genome.fitness.to_bits(), genome.activate([0.5, -1.0])))
+        .collect::<HashMap<_, _>>();
+
+    islands.migrate();
+
+    for (pop, arrivals) in islands.pops.iter().zip(arrivals) {
+        let mut ids = HashSet::new();
+        assert!(pop.genomes.iter().all(|genome| ids.insert(genome.lineage.id)));
+
+        let fitnesses = pop.genomes.iter().map(|genome| *genome.fitness).collect::<Vec<_>>();
+
+        for fitness in arrivals {
+            assert!(fitnesses.contains(fitness));
+        }
+
+        for genome in pop.genomes.iter() {
+            assert_eq!(genome.validate(&Conf::default()), vec![]);
+            assert_eq!(genome.activate([0.5, -1.0]), outputs[&genome.fitness.to_bits()]);
+
+            // every edge is numbered the way the island it lives on numbers it
+            for edge in genome.edges.iter() {
+                assert_eq!(pop.tracker.edges[&(edge.tail.innov(), edge.head.innov())], edge.innov);
+            }
+        }
+    }
+}
+
+#[test]
+fn islands_migrate_around_ring() {
+    check_migration(Topology::Ring, [&[209.0, 208.0], &[9.0, 8.0], &[109.0, 108.0]]);
+}
+
+#[test]
+fn islands_migrate_everywhere() {
+    // the migrants of a weaker island are the least fit genomes of a stronger island once they
+    // arrive, so they make way for the migrants of the next island
+    check_migration(Topology::Full, [&[109.0, 108.0, 209.0, 208.0], &[209.0, 208.0], &[109.0, 108.0]]);
+}
+
+#[test]
+fn champion_species_survives_stagnation() {
+    let mut pop = Pop::<2, 1>::new(Conf { pop_size: 4, max_stagnation: 0, ..Conf::default() });
+    pop.speciate();
+
+    // the species once did far better than it does now, and hasn't improved since
+    pop.species[0].best = Fitness::from(100.0);
+    pop.species[0].stagnation = 5;
+
+    pop.evaluate(|genome, rng| Fitness::from(1.0));
+    pop.evolve();
+
+    assert_eq!(pop.genomes.len(), 4);
+    assert_eq!(pop.species.len(), 1);
+}
+
+#[test]
+fn elitism_is_capped_by_species_size() {
+    let mut pop = Pop::<2, 1>::new(Conf { pop_size: 4, elitism: 2, compat_threshold: 0.3, ..Conf::default() });
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    // the only genome with any edges is a species of its own, and the fittest by far
+    pop.tracker.within(|| grow(&mut pop.genomes[0], &mut rng, 2));
+    let id = pop.genomes[0].lineage.id;
+
+    pop.evaluate(|genome, rng| Fitness::from(genome.edges.len() as f32));
+    pop.speciate();
+    assert!(pop.species.iter().any(|species| species.members == [0]));
+
+    pop.evolve();
+
+    assert_eq!(pop.genomes.len(), 4);
+    assert_eq!(pop.genomes.iter().filter(|genome| genome.lineage.id == id).count(), 1);
+}
+
+#[test]
+fn replace_swaps_out_the_worst_old_enough() {
+    let mut pop = Pop::<2, 1>::new(Conf { pop_size: 6, min_age: 2, ..Conf::default() });
+
+    // the least fit genomes are too young, which leaves the one at 4 as the least fit of the rest
+    for (genome, (age, fitness)) in pop.genomes.iter_mut().zip([(5, 3.0), (5, 4.0), (0, 0.0), (5, 2.0), (5, 1.0), (0, 0.5)]) {
+        genome.age = age;
+        genome.fitness = Fitness::from(fitness);
+    }
+
+    let next = pop.next_genome;
+    assert_eq!(pop.replace(), Some(4));
+
+    let child = &pop.genomes[4];
+    assert_eq!((child.age, child.fitness, child.lineage.id), (0, Fitness::default(), next));
+    assert!(!child.lineage.parents.is_empty());
+    assert!(pop.genomes.iter().enumerate().all(|(idx, genome)| idx == 4 || genome.age > 0));
+
+    // the child has been placed into a species, and every other genome is still where it was
+    for idx in 0..pop.genomes.len() {
+        assert_eq!(pop.species.iter().filter(|species| species.members.contains(&idx)).count(), 1);
+    }
+}
+
+#[test]
+fn replace_waits_for_min_age() {
+    let mut pop = Pop::<2, 1>::new(Conf { pop_size: 6, min_age: 3, ..Conf::default() });
+    let ids = pop.genomes.iter().map(|genome| genome.lineage.id).collect::<Vec<_>>();
+
+    assert_eq!(pop.replace(), None);
+    assert_eq!(pop.replace(), None);
+    assert!(pop.genomes.iter().map(|genome| genome.lineage.id).eq(ids.iter().copied()));
+
+    // every genome has now been around for long enough
+    assert!(pop.replace().is_some());
+}
+
+#[test]
+fn reproducible_runs() {
+    fn run(seed: u64) -> (Fitness, Vec<(usize, u32, bool)>) {
+        let mut pop = Pop::<2, 1>::new(Conf { seed, pop_size: 30, ..Conf::default() });
+
+        for _ in 0..10 {
+            pop.evaluate(|genome, rng| Fitness::from(genome.edges.len() as f32 + rng.random::<f32>()));
+            pop.evolve();
+        }
+
+        pop.evaluate(|genome, rng| Fitness::from(genome.edges.len() as f32 + rng.random::<f32>()));
+
+        let champion = pop.champion().unwrap();
+        let edges = champion.edges.iter().map(|edge| (edge.innov, edge.weight.to_bits(), edge.enabled.get())).collect();
+        (champion.fitness, edges)
+    }
+
+    let first = run(7);
+
+    // whatever else is numbered on the thread in between has no say in the second run
+    grow(&mut Genome::<2, 1>::default(), &mut SmallRng::seed_from_u64(0), 4);
+
+    assert_eq!(run(7), first);
+}
+
+#[test]
+fn resume_from_checkpoint() {
+    let eval = |genome: &Genome<2, 1>, rng: &mut SmallRng| Fitness::from(genome.edges.len() as f32 + rng.random::<f32>());
+    let conf = Conf { seed: 3, pop_size: 30, ..Conf::default() };
+    let mut pop = Pop::<2, 1>::new(conf.clone());
+    let mut checkpoint = None::<Checkpoint>;
+
+    for generation in 0..10 {
+        if generation == 5 {
+            checkpoint = Some(pop.checkpoint());
+        }
+
+        pop.evaluate(eval);
+        pop.evolve();
+    }
+
+    let uninterrupted = GenomeRepr::from(pop.champion().unwrap());
+
+    // resuming on a thread whose innovation numbers have moved on since
+    grow(&mut Genome::<2, 1>::default(), &mut SmallRng::seed_from_u64(0), 4);
+
+    let mut pop = Pop::<2, 1>::resume(&checkpoint.unwrap()).unwrap();
+
+    for _ in 5..10 {
+        pop.evaluate(eval);
+        pop.evolve();
+    }
+
+    assert_eq!(GenomeRepr::from(pop.champion().unwrap()), uninterrupted);
+}
+
+#[test]
+fn statistics_csv() {
+    #[derive(Default)]
+    struct Counts {
+        starts: usize,
+        ends: usize,
+        completed: bool,
+    }
+
+    impl Reporter<2, 1> for Counts {
+        fn generation_start(&mut self, generation: usize) { self.starts += 1; }
+        fn generation_end(&mut self, pop: &Pop<'_, 2, 1>) { self.ends += 1; }
+        fn run_complete(&mut self, pop: &Pop<'_, 2, 1>) { self.completed = true; }
+    }
+
+    let eval = |genome: &Genome<2, 1>, rng: &mut SmallRng| Fitness::from(genome.edges.len() as f32);
+
+    let mut counts = Counts::default();
+    Pop::<2, 1>::new(Conf { pop_size: 20, ..Conf::default() }).run(3, eval, &mut counts);
+    assert_eq!((counts.starts, counts.ends, counts.completed), (3, 3, true));
+
+    let mut stats = Statistics::default();
+    Pop::<2, 1>::new(Conf { pop_size: 20, ..Conf::default() }).run(4, eval, &mut stats);
+
+    assert_eq!(stats.generations.len(), 4);
+    assert!(stats.generations.iter().all(|stats| stats.species.iter().sum::<usize>() == 20));
+
+    let csv = stats.csv().to_string();
+    assert_eq!(csv.lines().count(), 1 + 4);
+    assert!(csv.lines().all(|line| line.split(',').count() == 8));
+}
+
+#[test]
+fn species_history() {
+    let eval = |genome: &Genome<2, 1>, rng: &mut SmallRng| Fitness::from(rng.random::<f32>());
+    let mut history = SpeciesHistory::default();
+    Pop::<2, 1>::new(Conf { pop_size: 20, compat_threshold: 0.5, ..Conf::default() }).run(5, eval, &mut history);
+
+    assert_eq!(history.generations.len(), 5);
+    assert!(history.parents.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+    // only the species founded by the first generation have no parent
+    assert!(history.parents.iter().filter(|(_, parent)| parent.is_none()).all(|(id, _)| history.generations[0].iter().any(|(curr, _)| curr == id)));
+
+    let csv = history.csv().to_string();
+    assert_eq!(csv.lines().count(), 1 + history.generations.iter().map(Vec::len).sum::<usize>());
+
+    let svg = history.svg().to_string();
+    assert_eq!(svg.matches("<polygon").count(), history.parents.len());
+}
+
+#[test]
+fn champion_ancestry() {
+    let eval = |genome: &Genome<2, 1>, rng: &mut SmallRng| Fitness::from(genome.edges.len() as f32 + rng.random::<f32>());
+    let mut phylogeny = Phylogeny::default();
+    let mut pop = Pop::<2, 1>::new(Conf { pop_size: 20, ..Conf::default() });
+
+    pop.run(6, eval, &mut phylogeny);
+    pop.evaluate(eval);
+
+    let champion = pop.champion().unwrap();
+    let ancestry = phylogeny.ancestry(champion.lineage.id);
+
+    assert_eq!(ancestry[0].id, champion.lineage.id);
+    assert_eq!(ancestry.last().unwrap().born, 0);
+    assert!(ancestry.windows(2).all(|pair| pair[0].born >= pair[1].born));
+    assert!(ancestry.iter().all(|lineage| lineage.parents.iter().all(|parent| ancestry.iter().any(|curr| curr.id == *parent))));
+
+    let dot = phylogeny.dot(champion.lineage.id).to_string();
+    assert_eq!(dot.matches(" -> ").count(), ancestry.iter().map(|lineage| lineage.parents.len()).sum::<usize>());
+}
+
+#[test]
+fn genome_dot() {
+    let mut genome = Genome::<2, 1>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 2);
+
+    let layers = genome.hiddens.iter().map(Node::layer)
+        .chain(genome.outputs.iter().map(Node::layer))
+        .chain([0])
+        .collect::<HashSet<_>>();
+
+    let dot = genome.dot().to_string();
+    assert!(dot.starts_with("digraph genome {"));
+    assert_eq!(dot.matches("rank=same").count(), layers.len());
+    assert_eq!(dot.matches(" -> ").count(), genome.edges.len());
+    assert!(dot.contains("style=dashed"));
+}
+
+#[test]
+fn genome_svg() {
+    let mut genome = Genome::<2, 1>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 2);
+
+    let svg = genome.svg().to_string();
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(svg.matches("<circle").count(), 2 + 1 + genome.hiddens.iter().count());
+    assert_eq!(svg.matches("<line").count(), genome.edges.len());
+    assert!(svg.contains("stroke-dasharray"));
+}
+
+#[test]
+fn genome_diff() {
+    let mut lhs = Genome::<2, 1>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut lhs, &mut rng, 3);
+
+    assert!(lhs.diff(&lhs).is_empty());
+
+    let mut rhs = lhs.clone();
+    grow(&mut rhs, &mut rng, 3);
+
+    let diff = lhs.diff(&rhs);
+    assert_eq!(diff.matching.len(), lhs.edges.len());
+    assert_eq!(lhs.edges.innov_matching(&rhs.edges).len(), lhs.edges.len());
+    assert_eq!(diff.disjoint.lhs, vec![]);
+    assert_eq!(diff.excess.lhs, vec![]);
+    assert_eq!(diff.disjoint.rhs.len() + diff.excess.rhs.len(), rhs.edges.len() - lhs.edges.len());
+    assert_eq!(rhs.edges.innov_disjoint(&lhs.edges).len(), diff.disjoint.rhs.len());
+    assert_eq!(rhs.edges.innov_excess(&lhs.edges).len(), diff.excess.rhs.len());
+    assert_eq!(diff.added.len(), rhs.hiddens.len() - lhs.hiddens.len());
+    assert_eq!(diff.removed, vec![]);
+
+    let reverse = rhs.diff(&lhs);
+    assert_eq!(reverse.removed, diff.added);
+
+    // the distance between two genomes is made up of exactly what their diff shows
+    let conf = Conf::default();
+    let len = rhs.edges.len() as f32;
+    let weight = diff.weight_deltas().map(|(_, delta)| delta.abs()).sum::<f32>() / diff.matching.len() as f32;
+    let excess = (diff.excess.lhs.len() + diff.excess.rhs.len()) as f32;
+    let disjoint = (diff.disjoint.lhs.len() + diff.disjoint.rhs.len()) as f32;
+    assert_eq!(lhs.compat_dist(&rhs, &conf), (conf.excess_coeff * excess / len) + (conf.disjoint_coeff * disjoint / len) + (conf.weight_coeff * weight));
+    assert_eq!(lhs.compat_dist(&rhs, &conf), rhs.compat_dist(&lhs, &conf));
+    assert!(diff.to_string().contains(&format!("{} matching edges", lhs.edges.len())));
+}
+
+#[test]
+fn genome_validate() {
+    let conf = Conf::default();
+    let mut genome = Genome::<3, 2>::default();
+    assert_eq!(genome.validate(&conf), vec![]);
+    assert_eq!(genome.outputs[1].index::<3>(), 1);
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 4);
+    assert_eq!(genome.validate(&conf), vec![]);
+
+    let hidden = genome.hiddens.iter().next().unwrap().downgrade().upgrade();
+    let other = Genome::<3, 2>::default();
+
+    let looped = Edge::recurrent(hidden, hidden);
+    let foreign = Edge::recurrent(&other.inputs[0], &other.outputs[0]);
+    let (looped_innov, foreign_innov) = (looped.innov, foreign.innov);
+    genome.edges.insert(looped);
+    genome.edges.insert(foreign);
+
+    let violations = genome.validate(&conf);
+    assert!(violations.contains(&Violation::Layer(looped_innov)));
+    assert!(violations.contains(&Violation::Cycle(looped_innov)));
+    assert!(violations.contains(&Violation::ForeignTail(foreign_innov)));
+    assert!(violations.contains(&Violation::ForeignHead(foreign_innov)));
+
+    let violations = genome.validate(&Conf { recurrent: true, ..Conf::default() });
+    assert!(!violations.contains(&Violation::Cycle(looped_innov)));
+}
+
+#[test]
+fn settle_layers_gives_up_on_cycles() {
+    let mut genome = Genome::<2, 1>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 6);
+    assert_eq!(genome.edges.settle_layers(), Ok(()));
+
+    let edge = genome.edges.iter()
+        .find(|edge| edge.tail.hidden().is_some() && edge.head.hidden().is_some())
+        .unwrap();
+
+    let tail = edge.tail.hidden().unwrap().downgrade().upgrade();
+    let head = edge.head.hidden().unwrap().downgrade().upgrade();
+    genome.edges.insert(Edge::new(head, tail));
+
+    assert!(matches!(genome.edges.settle_layers(), Err(Violation::Cycle(_))));
+}
+
+#[test]
+fn genome_repr_roundtrip() {
+    let mut genome = Genome::<3, 2>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 6);
+
+    let repr = GenomeRepr::from(&genome);
+    let restored = Genome::<3, 2>::try_from(&repr).unwrap();
+    assert_eq!(GenomeRepr::from(&restored), repr);
+
+    let inputs = [0.5, -1.0, 2.0];
+    assert_eq!(Network::from(&restored).activate(inputs), Network::from(&genome).activate(inputs));
+
+    assert_eq!(Genome::<2, 2>::try_from(&repr).unwrap_err(), ReprError::Inputs(3));
+
+    // outputs are numbered right after the inputs
+    assert!(genome.outputs.iter().map(|output| output.innov()).eq(3..5));
+
+    let mut misnumbered = repr.clone();
+    misnumbered.nodes.swap(3, 4);
+    assert_eq!(Genome::<3, 2>::try_from(&misnumbered).unwrap_err(), ReprError::Misnumbered(4));
+
+    let mut duplicate = repr.clone();
+    duplicate.edges.push(EdgeGene { weight: -1.0, ..repr.edges[0].clone() });
+    assert_eq!(Genome::<3, 2>::try_from(&duplicate).unwrap_err(), ReprError::DuplicateEdge(repr.edges[0].innov));
+
+    let innov = repr.edges.iter().map(|edge| edge.innov).max().unwrap() + 1;
+    duplicate.edges.last_mut().unwrap().innov = innov;
+    assert_eq!(Genome::<3, 2>::try_from(&duplicate).unwrap_err(), ReprError::DuplicateEdge(innov));
+    assert!(matches!(Genome::<3, 2>::try_from(&GenomeRepr::decode(&duplicate.encode().unwrap()).unwrap()), Err(ReprError::DuplicateEdge(_))));
+
+    let mut repr = repr;
+    repr.nodes[4].activation = "softplus".to_string();
+    assert_eq!(Genome::<3, 2>::try_from(&repr).unwrap_err(), ReprError::UnknownActivation("softplus".to_string()));
+}
+
+#[test]
+fn genome_repr_keeps_recurrent_edges() {
+    let node = |innov| NodeGene {
+        innov,
+        bias: 0.0,
+        response: 1.0,
+        tau: 1.0,
+        activation: "identity".to_string(),
+        aggregation: "sum".to_string(),
+    };
+
+    let edge = |innov, tail, head, recurrent| EdgeGene { tail, head, weight: 0.5, enabled: true, innov, recurrent };
+
+    // the back edge from 3 to 2 is numbered before the forward edge that it loops over
+    let repr = GenomeRepr {
+        inputs: 1,
+        outputs: 1,
+        nodes: (0..4).map(node).collect(),
+        edges: vec![edge(0, 0, 2, false), edge(1, 3, 2, true), edge(2, 2, 3, false), edge(3, 3, 1, false)],
+    };
+
+    let genome = Genome::<1, 1>::try_from(&repr).unwrap();
+    assert_eq!(GenomeRepr::from(&genome), repr);
+
+    let layers = genome.hiddens.iter().map(|hidden| (hidden.innov(), hidden.layer())).collect::<HashMap<_, _>>();
+    assert!(layers[&2] < layers[&3]);
+
+    let mut cyclic = repr.clone();
+    cyclic.edges[1].recurrent = false;
+    assert!(matches!(Genome::<1, 1>::try_from(&cyclic), Err(ReprError::Cycle(_))));
+
+    let conf = Conf { recurrent: true, ..Conf::default() };
+    let mut genome = Genome::<2, 1>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    for _ in 0..16 {
+        _ = genome.mutate_add_edge(&conf, &mut rng);
+        genome.mutate_split_edge(&conf, &mut rng);
+    }
+
+    assert!(genome.edges.iter().any(|edge| edge.recurrent));
+
+    let restored = Genome::<2, 1>::try_from(&GenomeRepr::from(&genome)).unwrap();
+    assert_eq!(GenomeRepr::from(&restored), GenomeRepr::from(&genome));
+    assert_eq!(restored.validate(&conf), vec![]);
+
+    let (mut lhs, mut rhs) = (RecurrentNetwork::from(&genome), RecurrentNetwork::from(&restored));
+
+    for inputs in [[1.0, -1.0], [0.5, 0.5], [0.0, 2.0]] {
+        assert_eq!(lhs.activate(inputs), rhs.activate(inputs));
+    }
+}
+
+#[test]
+fn genome_repr_registers_edge_innovs() {
+    let mut genome = Genome::<2, 2>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 6);
+
+    let repr = GenomeRepr::from(&genome);
+    let max = repr.edges.iter().map(|edge| edge.innov).max().unwrap();
+
+    // a fresh thread hasn't numbered any edges yet
+    std::thread::spawn(move || {
+        let mut genome = Genome::<2, 2>::try_from(&repr).unwrap();
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        while genome.mutate_add_edge(&Conf::default(), &mut rng).is_ok() {}
+
+        assert!(genome.edges.iter().filter(|edge| !repr.edges.iter().any(|gene| gene.innov == edge.innov)).all(|edge| edge.innov > max));
+        assert_eq!(genome.validate(&Conf::default()), vec![]);
+    }).join().unwrap();
+}
+
+#[test]
+fn genome_codec_roundtrip() {
+    let mut genome = Genome::<3, 2>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 6);
+
+    let repr = GenomeRepr::from(&genome);
+    let mut bytes = repr.encode().unwrap();
+    assert_eq!(GenomeRepr::decode(&bytes).unwrap(), repr);
+
+    // a field written by a later minor version is skipped
+    bytes.extend([200, 1, 3, 0xde, 0xad, 0xbe]);
+    assert_eq!(GenomeRepr::decode(&bytes).unwrap(), repr);
+
+    assert_eq!(GenomeRepr::decode(&bytes[..bytes.len() - 1]), Err(DecodeError::Truncated));
+    assert_eq!(GenomeRepr::decode(b"TAEN\x01\x00"), Err(DecodeError::Magic));
+
+    // version 1.0 ends right after the enabled bits, before the recurrent field
+    let mut bytes = repr.encode().unwrap();
+    bytes.truncate(bytes.len() - 2 - repr.edges.len().div_ceil(8));
+    bytes[5] = 0;
+    assert_eq!(GenomeRepr::decode(&bytes).unwrap(), repr);
+
+    let conf = Conf { recurrent: true, ..Conf::default() };
+    let mut genome = Genome::<2, 1>::default();
+
+    for _ in 0..16 {
+        _ = genome.mutate_add_edge(&conf, &mut rng);
+        genome.mutate_split_edge(&conf, &mut rng);
+    }
+
+    let repr = GenomeRepr::from(&genome);
+    assert!(repr.edges.iter().any(|edge| edge.recurrent));
+    assert_eq!(GenomeRepr::decode(&repr.encode().unwrap()).unwrap(), repr);
+    assert_eq!(GenomeRepr::decode(b"NEAT\x02\x00"), Err(DecodeError::Version(2)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn genome_serde_roundtrip() {
+    let mut genome = Genome::<2, 1>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 4);
+
+    let json = serde_json::to_string(&genome).unwrap();
+    let restored = serde_json::from_str::<Genome<2, 1>>(&json).unwrap();
+
+    assert_eq!(GenomeRepr::from(&restored), GenomeRepr::from(&genome));
+    assert!(serde_json::from_str::<Genome<3, 1>>(&json).is_err());
+}
+
+#[bench]
+fn activate_genome(b: &mut test::Bencher) {
+    let mut genome = Genome::<8, 4>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 16);
+
+    let rows = (0..4096).map(|_| rng.random::<[f32; 8]>()).collect::<Vec<_>>();
+
+    b.iter(|| rows.iter().map(|row| genome.activate(*row)).collect::<Vec<_>>());
+}
+
+#[bench]
+fn activate_network(b: &mut test::Bencher) {
+    let mut genome = Genome::<8, 4>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 16);
+
+    let network = Network::from(&genome);
+    let rows = (0..4096).map(|_| rng.random::<[f32; 8]>()).collect::<Vec<_>>();
+
+    b.iter(|| test::black_box(&rows).iter().map(|row| network.activate(*row)).collect::<Vec<_>>());
+}
+
+#[bench]
+fn activate_batch(b: &mut test::Bencher) {
+    let mut genome = Genome::<8, 4>::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+    grow(&mut genome, &mut rng, 16);
+
+    let network = Network::from(&genome);
+    let rows = (0..4096).map(|_| rng.random::<[f32; 8]>()).collect::<Vec<_>>();
+    let mut outputs = vec![[0.0; 4]; rows.len()];
+
+    b.iter(|| network.activate_batch(test::black_box(&rows), &mut outputs));
+}
@@ -0,0 +1,100 @@
+extern crate alloc;
+use crate::{conf::Conf, genome::Genome, node::*};
+use core::{fmt, ptr};
+use alloc::vec::Vec;
+use hashbrown::HashSet;
+
+/// Something that's wrong with a genome. Edges are identified by their innovation number.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    /// The tail of the edge isn't one of the genome's own nodes.
+    ForeignTail(usize),
+    /// The head of the edge isn't one of the genome's own nodes.
+    ForeignHead(usize),
+    /// More than one edge runs between the nodes with these innovation numbers.
+    DuplicateEdge(usize, usize),
+    /// The head of the edge isn't in a later layer than its tail.
+    Layer(usize),
+    /// The input at this index doesn't have the same innovation number.
+    InputInnov(usize),
+    /// The output at this index doesn't have the innovation number that follows the inputs.
+    OutputInnov(usize),
+    /// The edge closes a cycle without being allowed to.
+    Cycle(usize),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ForeignTail(innov) => write!(f, "edge {innov} has a tail that the genome doesn't own"),
+            Self::ForeignHead(innov) => write!(f, "edge {innov} has a head that the genome doesn't own"),
+            Self::DuplicateEdge(tail, head) => write!(f, "more than one edge runs from {tail} to {head}"),
+            Self::Layer(innov) => write!(f, "edge {innov} doesn't run to a later layer"),
+            Self::InputInnov(idx) => write!(f, "input {idx} has the wrong innovation number"),
+            Self::OutputInnov(idx) => write!(f, "output {idx} has the wrong innovation number"),
+            Self::Cycle(innov) => write!(f, "edge {innov} closes a cycle"),
+        }
+    }
+}
+
+impl<const I: usize, const O: usize> Genome<'_, I, O> {
+    /// Returns everything that's wrong with the genome. Recurrent edges are exempt from the layer
+    /// and cycle checks if `conf` allows them, but forward edges never are.
+    pub fn validate(&self, conf: &Conf) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        let inputs = self.inputs.iter().enumerate()
+            .filter(|(idx, input)| input.innov() != *idx)
+            .map(|(idx, _)| Violation::InputInnov(idx));
+
+        let outputs = self.outputs.iter().enumerate()
+            .filter(|(idx, output)| output.innov() != I + idx)
+            .map(|(idx, _)| Violation::OutputInnov(idx));
+
+        violations.extend(inputs.chain(outputs));
+
+        let owns_tail = |tail: &Tail| match tail {
+            Tail::Input(input) => self.inputs.iter().any(|curr| ptr::eq(curr, *input)),
+            Tail::Hidden(hidden) => self.hiddens.iter().any(|curr| ptr::eq(curr, *hidden)),
+        };
+
+        let owns_head = |head: &Head| match head {
+            Head::Hidden(hidden) => self.hiddens.iter().any(|curr| ptr::eq(curr, *hidden)),
+            Head::Output(output) => self.outputs.iter().any(|curr| ptr::eq(curr, *output)),
+        };
+
+        let mut pairs = HashSet::new();
+
+        for edge in self.edges.iter() {
+            let (tail, head) = (edge.tail.innov(), edge.head.innov());
+
+            if !owns_tail(&edge.tail) {
+                violations.push(Violation::ForeignTail(edge.innov));
+            }
+
+            if !owns_head(&edge.head) {
+                violations.push(Violation::ForeignHead(edge.innov));
+            }
+
+            // only reported once no matter how many copies there are
+            if !pairs.insert((tail, head)) && !violations.contains(&Violation::DuplicateEdge(tail, head)) {
+                violations.push(Violation::DuplicateEdge(tail, head));
+            }
+
+            // only a recurrent edge is free to feed back to any layer, and only if `conf` allows it
+            if edge.recurrent && conf.recurrent {
+                continue;
+            }
+
+            if edge.head.layer() <= edge.tail.layer() {
+                violations.push(Violation::Layer(edge.innov));
+            }
+
+            if edge.recurrent || self.edges.reaches(&edge.head, &edge.tail) {
+                violations.push(Violation::Cycle(edge.innov));
+            }
+        }
+
+        violations
+    }
+}